@@ -1,5 +1,5 @@
 use gtest::{Log, Program, System};
-use tamagotchi_interaction_io::{Tamagotchi, TmgAction};
+use tamagotchi_nft_io::TmgAction;
 #[test]
 fn smoke_test() {
     // test contract initialization
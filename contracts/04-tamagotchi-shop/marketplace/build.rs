@@ -0,0 +1,5 @@
+use marketplace_io::ProgramMetadata;
+
+fn main() {
+    gear_wasm_builder::build_with_metadata::<ProgramMetadata>();
+}
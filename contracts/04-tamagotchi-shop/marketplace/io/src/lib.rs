@@ -0,0 +1,128 @@
+#![no_std]
+
+use gmeta::{In, InOut, Metadata as GMetadata, Out};
+use gstd::{collections::BTreeMap, prelude::*, ActorId};
+use store_io::{AttributeId, Price, TamagotchiId, TransactionId};
+
+pub struct ProgramMetadata;
+
+impl GMetadata for ProgramMetadata {
+    type Init = In<MarketplaceInit>;
+    type Handle = InOut<MarketAction, MarketEvent>;
+    type Reply = ();
+    type Others = ();
+    type Signal = ();
+    type State = Out<Marketplace>;
+}
+
+#[derive(Encode, Decode, TypeInfo)]
+#[codec(crate = gstd::codec)]
+#[scale_info(crate = gstd::scale_info)]
+pub struct MarketplaceInit {
+    pub store_id: ActorId,
+    pub ft_contract_id: ActorId,
+    /// Part of every sale price paid to the store, in percent.
+    pub royalty_percent: u8,
+}
+
+#[derive(Default, Encode, Decode, TypeInfo)]
+#[codec(crate = gstd::codec)]
+#[scale_info(crate = gstd::scale_info)]
+pub struct Marketplace {
+    pub admin: ActorId,
+    pub store_id: ActorId,
+    pub ft_contract_id: ActorId,
+    pub royalty_percent: u8,
+    pub transaction_id: TransactionId,
+    /// Prices of the attributes put up for sale by their owners.
+    pub listings: BTreeMap<(TamagotchiId, AttributeId), Price>,
+    /// Purchases in progress by buyer.
+    pub purchases: BTreeMap<TamagotchiId, Purchase>,
+}
+
+#[derive(Encode, Decode, Clone, Copy, TypeInfo, Debug, PartialEq, Eq)]
+#[codec(crate = gstd::codec)]
+#[scale_info(crate = gstd::scale_info)]
+pub struct Purchase {
+    /// First of the transaction ids reserved for the token transfers of the purchase.
+    pub transaction_id: TransactionId,
+    pub seller: TamagotchiId,
+    pub attribute_id: AttributeId,
+    pub price: Price,
+    /// Part of the price paid to the store, fixed when the purchase starts.
+    pub royalty: Price,
+    pub stage: PurchaseStage,
+}
+
+/// Step a purchase is retried from if it didn't complete.
+#[derive(Encode, Decode, Clone, Copy, TypeInfo, Debug, PartialEq, Eq)]
+#[codec(crate = gstd::codec)]
+#[scale_info(crate = gstd::scale_info)]
+pub enum PurchaseStage {
+    /// The buyer pays the price to the marketplace.
+    Payment,
+    /// The store moves the attribute to the buyer.
+    Transfer,
+    /// The marketplace pays the seller and the royalty.
+    Payout,
+    /// The store books the royalty it was paid.
+    Royalty,
+    /// The store refused the transfer, the marketplace pays the price back.
+    Refund,
+}
+
+#[derive(Encode, Decode, TypeInfo)]
+#[codec(crate = gstd::codec)]
+#[scale_info(crate = gstd::scale_info)]
+pub enum MarketAction {
+    List {
+        attribute_id: AttributeId,
+        price: Price,
+    },
+    CancelListing {
+        attribute_id: AttributeId,
+    },
+    Buy {
+        seller: TamagotchiId,
+        attribute_id: AttributeId,
+    },
+    SetRoyalty {
+        royalty_percent: u8,
+    },
+}
+
+#[derive(Encode, Decode, TypeInfo, Debug, PartialEq, Eq)]
+#[codec(crate = gstd::codec)]
+#[scale_info(crate = gstd::scale_info)]
+pub enum MarketEvent {
+    Listed {
+        seller: TamagotchiId,
+        attribute_id: AttributeId,
+        price: Price,
+    },
+    ListingCancelled {
+        seller: TamagotchiId,
+        attribute_id: AttributeId,
+    },
+    Sold {
+        store_id: ActorId,
+        seller: TamagotchiId,
+        buyer: TamagotchiId,
+        attribute_id: AttributeId,
+        price: Price,
+    },
+    PurchaseFailed {
+        seller: TamagotchiId,
+        attribute_id: AttributeId,
+    },
+    CompletePrevPurchase {
+        seller: TamagotchiId,
+        attribute_id: AttributeId,
+    },
+    NotOwned {
+        attribute_id: AttributeId,
+    },
+    RoyaltySet {
+        royalty_percent: u8,
+    },
+}
@@ -0,0 +1,370 @@
+#![no_std]
+
+use gstd::{collections::BTreeMap, exec, msg, prelude::*, ActorId};
+use marketplace_io::{MarketAction, MarketEvent, MarketplaceInit, Purchase, PurchaseStage};
+use sharded_fungible_token_io::{FTokenAction, FTokenEvent, LogicAction};
+use store_io::{
+    percent_of, AttributeId, Price, StoreAction, StoreEvent, TamagotchiId, TransactionId,
+};
+
+/// Payment, seller payout, royalty and refund each use their own transaction id.
+const TRANSACTIONS_PER_PURCHASE: TransactionId = 4;
+
+static mut MARKETPLACE: Option<Marketplace> = None;
+
+#[derive(Default)]
+struct Marketplace {
+    admin: ActorId,
+    store_id: ActorId,
+    ft_contract_id: ActorId,
+    royalty_percent: u8,
+    transaction_id: TransactionId,
+    listings: BTreeMap<(TamagotchiId, AttributeId), Price>,
+    purchases: BTreeMap<TamagotchiId, Purchase>,
+}
+
+impl Marketplace {
+    async fn list(&mut self, attribute_id: AttributeId, price: Price) {
+        assert!(price > 0, "Price must be greater than zero");
+        let seller = msg::source();
+
+        if !self.owns(&seller, attribute_id).await {
+            msg::reply(MarketEvent::NotOwned { attribute_id }, 0)
+                .expect("Error in sending a reply `MarketEvent::NotOwned`");
+            return;
+        }
+
+        self.listings.insert((seller, attribute_id), price);
+        msg::reply(
+            MarketEvent::Listed {
+                seller,
+                attribute_id,
+                price,
+            },
+            0,
+        )
+        .expect("Error in sending a reply `MarketEvent::Listed`");
+    }
+
+    fn cancel_listing(&mut self, attribute_id: AttributeId) {
+        let seller = msg::source();
+        self.listings
+            .remove(&(seller, attribute_id))
+            .expect("The attribute isn`t listed");
+
+        msg::reply(
+            MarketEvent::ListingCancelled {
+                seller,
+                attribute_id,
+            },
+            0,
+        )
+        .expect("Error in sending a reply `MarketEvent::ListingCancelled`");
+    }
+
+    /// Sells a listed attribute through the marketplace escrow.
+    ///
+    /// The buyer pays the marketplace, the store moves the attribute, then the seller gets
+    /// the price less the royalty and the store gets the royalty, which it books once the
+    /// marketplace confirms the payment. If the store refuses the
+    /// move, the buyer is paid back. An interrupted purchase is retried from the step it
+    /// stopped at with the same transaction ids, a transfer that was interrupted after the
+    /// store moved the attribute goes on to the payout.
+    async fn buy(&mut self, seller: &TamagotchiId, attribute_id: AttributeId) {
+        let buyer = msg::source();
+        let resumed = self.purchases.contains_key(&buyer);
+        let mut purchase = if let Some(purchase) = self.purchases.get(&buyer) {
+            if purchase.seller != *seller || purchase.attribute_id != attribute_id {
+                msg::reply(
+                    MarketEvent::CompletePrevPurchase {
+                        seller: purchase.seller,
+                        attribute_id: purchase.attribute_id,
+                    },
+                    0,
+                )
+                .expect("Error in sending a reply `MarketEvent::CompletePrevPurchase`");
+                return;
+            }
+            *purchase
+        } else {
+            assert_ne!(buyer, *seller, "Sellers can`t buy their own attributes");
+            let price = self
+                .listings
+                .remove(&(*seller, attribute_id))
+                .expect("The attribute isn`t listed");
+            let purchase = Purchase {
+                transaction_id: self.transaction_id,
+                seller: *seller,
+                attribute_id,
+                price,
+                royalty: percent_of(price, self.royalty_percent),
+                stage: PurchaseStage::Payment,
+            };
+            self.transaction_id = self.transaction_id.wrapping_add(TRANSACTIONS_PER_PURCHASE);
+            self.purchases.insert(buyer, purchase);
+            purchase
+        };
+
+        let event = loop {
+            purchase.stage = match purchase.stage {
+                PurchaseStage::Payment => {
+                    if self
+                        .transfer_tokens(
+                            purchase.transaction_id,
+                            &buyer,
+                            &exec::program_id(),
+                            purchase.price,
+                        )
+                        .await
+                        .is_err()
+                    {
+                        // the buyer keeps the tokens and the attribute goes back on sale
+                        self.purchases.remove(&buyer);
+                        self.listings
+                            .insert((*seller, attribute_id), purchase.price);
+                        break MarketEvent::PurchaseFailed {
+                            seller: *seller,
+                            attribute_id,
+                        };
+                    }
+                    PurchaseStage::Transfer
+                }
+                PurchaseStage::Transfer => {
+                    // the store may have moved the attribute before its reply was lost
+                    let moved = resumed
+                        && self.owns(&buyer, attribute_id).await
+                        && !self.owns(seller, attribute_id).await;
+                    if moved {
+                        PurchaseStage::Payout
+                    } else {
+                        let reply = msg::send_for_reply_as::<_, StoreEvent>(
+                            self.store_id,
+                            StoreAction::MarketplaceTransfer {
+                                from: *seller,
+                                to: buyer,
+                                attribute_id,
+                                royalty: purchase.royalty,
+                            },
+                            0,
+                            0,
+                        )
+                        .expect("Error in sending a message `StoreAction::MarketplaceTransfer`")
+                        .await;
+
+                        if let Ok(StoreEvent::AttributeTransferred { .. }) = reply {
+                            PurchaseStage::Payout
+                        } else {
+                            PurchaseStage::Refund
+                        }
+                    }
+                }
+                PurchaseStage::Payout => {
+                    let paid = self
+                        .transfer_tokens(
+                            purchase.transaction_id + 1,
+                            &exec::program_id(),
+                            seller,
+                            purchase.price - purchase.royalty,
+                        )
+                        .await
+                        .is_ok()
+                        && self
+                            .transfer_tokens(
+                                purchase.transaction_id + 2,
+                                &exec::program_id(),
+                                &self.store_id,
+                                purchase.royalty,
+                            )
+                            .await
+                            .is_ok();
+                    if !paid {
+                        break MarketEvent::CompletePrevPurchase {
+                            seller: *seller,
+                            attribute_id,
+                        };
+                    }
+                    PurchaseStage::Royalty
+                }
+                PurchaseStage::Royalty => {
+                    if purchase.royalty > 0 {
+                        let reply = msg::send_for_reply_as::<_, StoreEvent>(
+                            self.store_id,
+                            StoreAction::ConfirmRoyalty {
+                                royalty: purchase.royalty,
+                            },
+                            0,
+                            0,
+                        )
+                        .expect("Error in sending a message `StoreAction::ConfirmRoyalty`")
+                        .await;
+                        if !matches!(reply, Ok(StoreEvent::RoyaltyReceived { .. })) {
+                            break MarketEvent::CompletePrevPurchase {
+                                seller: *seller,
+                                attribute_id,
+                            };
+                        }
+                    }
+
+                    self.purchases.remove(&buyer);
+                    break MarketEvent::Sold {
+                        store_id: self.store_id,
+                        seller: *seller,
+                        buyer,
+                        attribute_id,
+                        price: purchase.price,
+                    };
+                }
+                PurchaseStage::Refund => {
+                    if self
+                        .transfer_tokens(
+                            purchase.transaction_id + 3,
+                            &exec::program_id(),
+                            &buyer,
+                            purchase.price,
+                        )
+                        .await
+                        .is_err()
+                    {
+                        break MarketEvent::CompletePrevPurchase {
+                            seller: *seller,
+                            attribute_id,
+                        };
+                    }
+
+                    self.purchases.remove(&buyer);
+                    break MarketEvent::PurchaseFailed {
+                        seller: *seller,
+                        attribute_id,
+                    };
+                }
+            };
+            self.purchases.insert(buyer, purchase);
+        };
+
+        msg::reply(event, 0).expect("Error in sending a reply `MarketEvent`");
+    }
+
+    fn set_royalty(&mut self, royalty_percent: u8) {
+        assert_eq!(msg::source(), self.admin, "Only admin can set the royalty");
+        assert!(royalty_percent <= 100, "Royalty can`t exceed 100 percent");
+        self.royalty_percent = royalty_percent;
+
+        msg::reply(MarketEvent::RoyaltySet { royalty_percent }, 0)
+            .expect("Error in sending a reply `MarketEvent::RoyaltySet`");
+    }
+
+    async fn owns(&self, tamagotchi_id: &TamagotchiId, attribute_id: AttributeId) -> bool {
+        let reply = msg::send_for_reply_as::<_, StoreEvent>(
+            self.store_id,
+            StoreAction::GetAttributes {
+                tamagotchi_id: *tamagotchi_id,
+            },
+            0,
+            0,
+        )
+        .expect("Error in sending a message `StoreAction::GetAttributes`")
+        .await;
+
+        matches!(
+            reply,
+            Ok(StoreEvent::Attributes { attributes }) if attributes.contains(&attribute_id)
+        )
+    }
+
+    /// Zero amounts aren't sent to the token contract.
+    async fn transfer_tokens(
+        &self,
+        transaction_id: TransactionId,
+        from: &ActorId,
+        to: &ActorId,
+        amount: u128,
+    ) -> Result<(), ()> {
+        if amount == 0 {
+            return Ok(());
+        }
+
+        let reply = msg::send_for_reply_as::<_, FTokenEvent>(
+            self.ft_contract_id,
+            FTokenAction::Message {
+                transaction_id,
+                payload: LogicAction::Transfer {
+                    sender: *from,
+                    recipient: *to,
+                    amount,
+                },
+            },
+            0,
+            0,
+        )
+        .expect("Error in sending a message `FTokenAction::Message`")
+        .await;
+
+        match reply {
+            Ok(FTokenEvent::Ok) => Ok(()),
+            _ => Err(()),
+        }
+    }
+}
+
+#[gstd::async_main]
+async fn main() {
+    let action: MarketAction = msg::load().expect("Unable to decode `MarketAction`");
+    let marketplace: &mut Marketplace = unsafe {
+        MARKETPLACE
+            .as_mut()
+            .expect("The contract is not initialized")
+    };
+    match action {
+        MarketAction::List {
+            attribute_id,
+            price,
+        } => marketplace.list(attribute_id, price).await,
+        MarketAction::CancelListing { attribute_id } => marketplace.cancel_listing(attribute_id),
+        MarketAction::Buy {
+            seller,
+            attribute_id,
+        } => marketplace.buy(&seller, attribute_id).await,
+        MarketAction::SetRoyalty { royalty_percent } => marketplace.set_royalty(royalty_percent),
+    }
+}
+
+#[no_mangle]
+extern fn init() {
+    let MarketplaceInit {
+        store_id,
+        ft_contract_id,
+        royalty_percent,
+    } = msg::load().expect("Unable to decode `MarketplaceInit`");
+    assert!(royalty_percent <= 100, "Royalty can`t exceed 100 percent");
+
+    let marketplace = Marketplace {
+        admin: msg::source(),
+        store_id,
+        ft_contract_id,
+        royalty_percent,
+        ..Default::default()
+    };
+    unsafe { MARKETPLACE = Some(marketplace) };
+}
+
+#[no_mangle]
+extern fn state() {
+    let marketplace = unsafe {
+        MARKETPLACE
+            .as_ref()
+            .expect("The contract is not initialized")
+    };
+    msg::reply(
+        marketplace_io::Marketplace {
+            admin: marketplace.admin,
+            store_id: marketplace.store_id,
+            ft_contract_id: marketplace.ft_contract_id,
+            royalty_percent: marketplace.royalty_percent,
+            transaction_id: marketplace.transaction_id,
+            listings: marketplace.listings.clone(),
+            purchases: marketplace.purchases.clone(),
+        },
+        0,
+    )
+    .expect("Failed to share state");
+}
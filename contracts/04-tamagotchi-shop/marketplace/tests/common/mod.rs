@@ -0,0 +1,193 @@
+#![allow(dead_code)]
+
+use gstd::ActorId;
+use gtest::{Log, Program, RunResult, System};
+use marketplace_io::{MarketAction, MarketEvent, Marketplace, MarketplaceInit};
+use sharded_fungible_token_io::{FTokenAction, FTokenEvent, InitFToken, LogicAction};
+use store_io::{AttrMetadata, AttributeStore, StoreAction, StoreEvent};
+
+pub const FT_MAIN_WASM: &str =
+    "../../target/wasm32-unknown-unknown/debug/sharded_fungible_token.opt.wasm";
+pub const FT_LOGIC_WASM: &str =
+    "../../target/wasm32-unknown-unknown/debug/sharded_fungible_token_logic.opt.wasm";
+pub const FT_STORAGE_WASM: &str =
+    "../../target/wasm32-unknown-unknown/debug/sharded_fungible_token_storage.opt.wasm";
+pub const STORE_WASM: &str = "../../target/wasm32-unknown-unknown/debug/store.opt.wasm";
+
+pub const ADMIN: u64 = 2;
+pub const SELLER: u64 = 3;
+pub const BUYER: u64 = 4;
+pub const STRANGER: u64 = 5;
+/// Account without a program, the marketplace waits for replies from it until a test sends them.
+pub const SILENT_STORE: u64 = 9;
+pub const FT_ID: u64 = 10;
+pub const STORE_ID: u64 = 11;
+pub const MARKETPLACE_ID: u64 = 12;
+
+pub const SWORD: u32 = 1;
+pub const STORE_PRICE: u128 = 100;
+pub const MARKET_PRICE: u128 = 200;
+pub const ROYALTY_PERCENT: u8 = 10;
+pub const BALANCE: u128 = 1000;
+
+pub fn init_ft(sys: &System) -> Program<'_> {
+    let storage_code_id = sys.submit_code(FT_STORAGE_WASM);
+    let logic_code_id = sys.submit_code(FT_LOGIC_WASM);
+    let ft = Program::from_file_with_id(sys, FT_ID, FT_MAIN_WASM);
+    let res = ft.send(
+        ADMIN,
+        InitFToken {
+            storage_code_hash: storage_code_id.into_bytes().into(),
+            ft_logic_code_hash: logic_code_id.into_bytes().into(),
+        },
+    );
+    assert!(!res.main_failed());
+
+    for (account, approved_account) in [(SELLER, STORE_ID), (BUYER, MARKETPLACE_ID)] {
+        for (transaction_id, payload) in [
+            LogicAction::Mint {
+                recipient: account.into(),
+                amount: BALANCE,
+            },
+            LogicAction::Approve {
+                approved_account: approved_account.into(),
+                amount: BALANCE,
+            },
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            let res = ft.send(
+                account,
+                FTokenAction::Message {
+                    transaction_id: transaction_id as u64,
+                    payload,
+                },
+            );
+            assert!(res.contains(&Log::builder().dest(account).payload(FTokenEvent::Ok)));
+        }
+    }
+    ft
+}
+
+/// The seller owns a sword bought in the store.
+pub fn init_store(sys: &System) -> Program<'_> {
+    let store = Program::from_file_with_id(sys, STORE_ID, STORE_WASM);
+    let res = store.send(ADMIN, ActorId::from(FT_ID));
+    assert!(!res.main_failed());
+
+    let res = store.send(
+        ADMIN,
+        StoreAction::CreateAttribute {
+            attribute_id: SWORD,
+            attr_metadata: AttrMetadata {
+                title: String::from("Sword"),
+                description: String::new(),
+                media: String::new(),
+                slot: None,
+                effects: None,
+            },
+            price: STORE_PRICE,
+        },
+    );
+    assert!(!res.main_failed());
+
+    let res = store.send(
+        SELLER,
+        StoreAction::BuyAttribute {
+            attribute_id: SWORD,
+            coupon: None,
+        },
+    );
+    assert!(res.contains(
+        &Log::builder()
+            .dest(SELLER)
+            .payload(StoreEvent::AttributeSold { success: true })
+    ));
+    store
+}
+
+pub fn init_marketplace(sys: &System, store_id: u64) -> Program<'_> {
+    let marketplace = Program::current_with_id(sys, MARKETPLACE_ID);
+    let res = marketplace.send(
+        ADMIN,
+        MarketplaceInit {
+            store_id: store_id.into(),
+            ft_contract_id: FT_ID.into(),
+            royalty_percent: ROYALTY_PERCENT,
+        },
+    );
+    assert!(!res.main_failed());
+    marketplace
+}
+
+pub fn trust_marketplace(store: &Program<'_>) {
+    let res = store.send(
+        ADMIN,
+        StoreAction::AddMarketplace {
+            marketplace: MARKETPLACE_ID.into(),
+        },
+    );
+    assert!(!res.main_failed());
+}
+
+pub fn list_sword(marketplace: &Program<'_>) {
+    let res = marketplace.send(
+        SELLER,
+        MarketAction::List {
+            attribute_id: SWORD,
+            price: MARKET_PRICE,
+        },
+    );
+    assert!(
+        res.contains(&Log::builder().dest(SELLER).payload(MarketEvent::Listed {
+            seller: SELLER.into(),
+            attribute_id: SWORD,
+            price: MARKET_PRICE,
+        }))
+    );
+}
+
+pub fn buy_sword(marketplace: &Program<'_>, buyer: u64, event: MarketEvent) {
+    let res = marketplace.send(
+        buyer,
+        MarketAction::Buy {
+            seller: SELLER.into(),
+            attribute_id: SWORD,
+        },
+    );
+    assert!(res.contains(&Log::builder().dest(buyer).payload(event)));
+}
+
+/// Replies to a message the marketplace sent to the silent store.
+pub fn reply_as_store(sys: &System, action: StoreAction, event: StoreEvent) -> RunResult {
+    sys.get_mailbox(SILENT_STORE)
+        .reply(
+            Log::builder()
+                .source(MARKETPLACE_ID)
+                .dest(SILENT_STORE)
+                .payload(action),
+            event,
+            0,
+        )
+        .expect("Unable to reply as the store")
+}
+
+pub fn assert_balance(ft: &Program<'_>, account: u64, balance: u128) {
+    let res = ft.send(ADMIN, FTokenAction::GetBalance(account.into()));
+    assert!(res.contains(
+        &Log::builder()
+            .dest(ADMIN)
+            .payload(FTokenEvent::Balance(balance))
+    ));
+}
+
+pub fn read_state(marketplace: &Program<'_>) -> Marketplace {
+    marketplace
+        .read_state(0)
+        .expect("Unable to read the marketplace state")
+}
+
+pub fn read_store(store: &Program<'_>) -> AttributeStore {
+    store.read_state(0).expect("Unable to read the store state")
+}
@@ -0,0 +1,262 @@
+mod common;
+
+use common::{
+    assert_balance, buy_sword, init_ft, init_marketplace, init_store, list_sword, read_state,
+    read_store, reply_as_store, trust_marketplace, BALANCE, BUYER, MARKETPLACE_ID, MARKET_PRICE,
+    ROYALTY_PERCENT, SELLER, SILENT_STORE, STORE_ID, STORE_PRICE, STRANGER, SWORD,
+};
+use gstd::ActorId;
+use gtest::{Log, System};
+use marketplace_io::{MarketAction, MarketEvent, PurchaseStage};
+use store_io::{StoreAction, StoreEvent};
+
+#[test]
+fn only_owner_lists_attribute() {
+    let sys = System::new();
+    sys.init_logger();
+    let _ft = init_ft(&sys);
+    let _store = init_store(&sys);
+    let marketplace = init_marketplace(&sys, STORE_ID);
+
+    let res = marketplace.send(
+        BUYER,
+        MarketAction::List {
+            attribute_id: SWORD,
+            price: MARKET_PRICE,
+        },
+    );
+    assert!(
+        res.contains(&Log::builder().dest(BUYER).payload(MarketEvent::NotOwned {
+            attribute_id: SWORD
+        }))
+    );
+
+    list_sword(&marketplace);
+    let res = marketplace.send(
+        SELLER,
+        MarketAction::CancelListing {
+            attribute_id: SWORD,
+        },
+    );
+    assert!(!res.main_failed());
+    let state = read_state(&marketplace);
+    assert!(state.listings.is_empty());
+}
+
+#[test]
+fn listed_attribute_is_sold_with_royalty() {
+    let sys = System::new();
+    sys.init_logger();
+    let ft = init_ft(&sys);
+    let store = init_store(&sys);
+    let marketplace = init_marketplace(&sys, STORE_ID);
+    trust_marketplace(&store);
+
+    list_sword(&marketplace);
+    buy_sword(
+        &marketplace,
+        BUYER,
+        MarketEvent::Sold {
+            store_id: STORE_ID.into(),
+            seller: SELLER.into(),
+            buyer: BUYER.into(),
+            attribute_id: SWORD,
+            price: MARKET_PRICE,
+        },
+    );
+
+    let royalty = MARKET_PRICE * u128::from(ROYALTY_PERCENT) / 100;
+    assert_balance(&ft, BUYER, BALANCE - MARKET_PRICE);
+    assert_balance(&ft, SELLER, BALANCE - STORE_PRICE + MARKET_PRICE - royalty);
+    assert_balance(&ft, STORE_ID, STORE_PRICE + royalty);
+    assert_balance(&ft, MARKETPLACE_ID, 0);
+
+    let state = read_store(&store);
+    assert!(!state.owners.contains_key(&ActorId::from(SELLER)));
+    assert_eq!(state.owners[&ActorId::from(BUYER)], [SWORD].into());
+    assert_eq!(state.revenue, STORE_PRICE + royalty);
+    assert!(state.royalties_due.is_empty());
+
+    let state = read_state(&marketplace);
+    assert!(state.listings.is_empty());
+    assert!(state.purchases.is_empty());
+}
+
+#[test]
+fn failed_payment_keeps_listing() {
+    let sys = System::new();
+    sys.init_logger();
+    let _ft = init_ft(&sys);
+    let store = init_store(&sys);
+    let marketplace = init_marketplace(&sys, STORE_ID);
+    trust_marketplace(&store);
+
+    list_sword(&marketplace);
+    buy_sword(
+        &marketplace,
+        STRANGER,
+        MarketEvent::PurchaseFailed {
+            seller: SELLER.into(),
+            attribute_id: SWORD,
+        },
+    );
+
+    let state = read_state(&marketplace);
+    assert_eq!(
+        state.listings[&(ActorId::from(SELLER), SWORD)],
+        MARKET_PRICE
+    );
+    assert!(state.purchases.is_empty());
+}
+
+#[test]
+fn untrusted_marketplace_refunds_buyer() {
+    let sys = System::new();
+    sys.init_logger();
+    let ft = init_ft(&sys);
+    let store = init_store(&sys);
+    let marketplace = init_marketplace(&sys, STORE_ID);
+
+    list_sword(&marketplace);
+    buy_sword(
+        &marketplace,
+        BUYER,
+        MarketEvent::PurchaseFailed {
+            seller: SELLER.into(),
+            attribute_id: SWORD,
+        },
+    );
+
+    assert_balance(&ft, BUYER, BALANCE);
+    let state = read_store(&store);
+    assert_eq!(state.owners[&ActorId::from(SELLER)], [SWORD].into());
+}
+
+#[test]
+fn royalty_of_large_price() {
+    let sys = System::new();
+    sys.init_logger();
+    let _ft = init_ft(&sys);
+    let store = init_store(&sys);
+    let marketplace = init_marketplace(&sys, STORE_ID);
+    trust_marketplace(&store);
+
+    let res = marketplace.send(
+        SELLER,
+        MarketAction::List {
+            attribute_id: SWORD,
+            price: u128::MAX,
+        },
+    );
+    assert!(!res.main_failed());
+    // the buyer can't pay, but the royalty is computed without overflowing
+    buy_sword(
+        &marketplace,
+        BUYER,
+        MarketEvent::PurchaseFailed {
+            seller: SELLER.into(),
+            attribute_id: SWORD,
+        },
+    );
+}
+
+#[test]
+fn resumed_transfer_pays_seller_if_attribute_moved() {
+    let sys = System::new();
+    sys.init_logger();
+    let ft = init_ft(&sys);
+    let marketplace = init_marketplace(&sys, SILENT_STORE);
+
+    let res = marketplace.send(
+        SELLER,
+        MarketAction::List {
+            attribute_id: SWORD,
+            price: MARKET_PRICE,
+        },
+    );
+    assert!(!res.main_failed());
+    let res = reply_as_store(
+        &sys,
+        StoreAction::GetAttributes {
+            tamagotchi_id: SELLER.into(),
+        },
+        StoreEvent::Attributes {
+            attributes: [SWORD].into(),
+        },
+    );
+    assert!(
+        res.contains(&Log::builder().dest(SELLER).payload(MarketEvent::Listed {
+            seller: SELLER.into(),
+            attribute_id: SWORD,
+            price: MARKET_PRICE,
+        }))
+    );
+
+    // the buyer pays, but the store never replies to the transfer
+    let res = marketplace.send(
+        BUYER,
+        MarketAction::Buy {
+            seller: SELLER.into(),
+            attribute_id: SWORD,
+        },
+    );
+    assert!(!res.main_failed());
+    let state = read_state(&marketplace);
+    assert_eq!(
+        state.purchases[&ActorId::from(BUYER)].stage,
+        PurchaseStage::Transfer
+    );
+
+    // the retry finds the sword moved and pays the seller instead of refunding the buyer
+    let res = marketplace.send(
+        BUYER,
+        MarketAction::Buy {
+            seller: SELLER.into(),
+            attribute_id: SWORD,
+        },
+    );
+    assert!(!res.main_failed());
+    reply_as_store(
+        &sys,
+        StoreAction::GetAttributes {
+            tamagotchi_id: BUYER.into(),
+        },
+        StoreEvent::Attributes {
+            attributes: [SWORD].into(),
+        },
+    );
+    reply_as_store(
+        &sys,
+        StoreAction::GetAttributes {
+            tamagotchi_id: SELLER.into(),
+        },
+        StoreEvent::Attributes {
+            attributes: Default::default(),
+        },
+    );
+    let royalty = MARKET_PRICE * u128::from(ROYALTY_PERCENT) / 100;
+    let res = reply_as_store(
+        &sys,
+        StoreAction::ConfirmRoyalty { royalty },
+        StoreEvent::RoyaltyReceived {
+            marketplace: MARKETPLACE_ID.into(),
+            royalty,
+        },
+    );
+    assert!(
+        res.contains(&Log::builder().dest(BUYER).payload(MarketEvent::Sold {
+            store_id: SILENT_STORE.into(),
+            seller: SELLER.into(),
+            buyer: BUYER.into(),
+            attribute_id: SWORD,
+            price: MARKET_PRICE,
+        }))
+    );
+
+    assert_balance(&ft, BUYER, BALANCE - MARKET_PRICE);
+    assert_balance(&ft, SELLER, BALANCE + MARKET_PRICE - royalty);
+    assert_balance(&ft, SILENT_STORE, royalty);
+    assert_balance(&ft, MARKETPLACE_ID, 0);
+    let state = read_state(&marketplace);
+    assert!(state.purchases.is_empty());
+}
@@ -1,5 +1,6 @@
 #![no_std]
 
+use blake2::{digest::consts::U32, Blake2b, Digest};
 use gmeta::{In, InOut, Metadata as GMetadata, Out};
 use gstd::{
     collections::{BTreeMap, BTreeSet},
@@ -11,6 +12,17 @@ pub type AttributeId = u32;
 pub type Price = u128;
 pub type TamagotchiId = ActorId;
 pub type TransactionId = u64;
+pub type CampaignId = u32;
+pub type BundleId = u32;
+pub type CouponHash = [u8; 32];
+
+/// Most entries a paginated query returns.
+pub const MAX_PAGE_SIZE: u32 = 50;
+
+/// Hash under which the store keeps a coupon code.
+pub fn coupon_hash(code: &str) -> CouponHash {
+    Blake2b::<U32>::digest(code.as_bytes()).into()
+}
 
 pub struct ProgramMetadata;
 
@@ -32,7 +44,129 @@ pub struct AttributeStore {
     pub attributes: BTreeMap<AttributeId, (AttrMetadata, Price)>,
     pub owners: BTreeMap<TamagotchiId, BTreeSet<AttributeId>>,
     pub transaction_id: TransactionId,
-    pub transactions: BTreeMap<TamagotchiId, (TransactionId, AttributeId)>,
+    pub transactions: BTreeMap<TamagotchiId, PendingTx>,
+    /// Attributes that stay owned but can no longer be bought.
+    pub delisted: BTreeSet<AttributeId>,
+    pub sales: BTreeMap<AttributeId, SaleLimits>,
+    /// Account that becomes the admin once it accepts the role.
+    pub pending_admin: Option<ActorId>,
+    /// Accounts that manage attributes along with the admin.
+    pub operators: BTreeSet<ActorId>,
+    pub treasury: Option<ActorId>,
+    /// Tokens earned from sales, the store balance is `revenue - withdrawn - refunded`.
+    pub revenue: u128,
+    pub withdrawn: u128,
+    pub receipts: BTreeMap<(TamagotchiId, AttributeId), Receipt>,
+    /// Number of blocks after a purchase during which the attribute can be returned.
+    pub return_windows: BTreeMap<AttributeId, u32>,
+    pub refund_transactions: BTreeMap<TamagotchiId, (TransactionId, AttributeId)>,
+    pub refunded: u128,
+    pub campaigns: BTreeMap<CampaignId, Campaign>,
+    pub coupons: BTreeMap<CouponHash, Discount>,
+    pub bundles: BTreeMap<BundleId, Bundle>,
+    /// Pending bundle purchases with the bundle as it was when the purchase started.
+    pub bundle_transactions: BTreeMap<TamagotchiId, (TransactionId, BundleId, Bundle)>,
+    /// Marketplace programs trusted to move attributes between tamagotchis.
+    pub marketplaces: BTreeSet<ActorId>,
+    /// Royalties of the transfers a marketplace made that it hasn't paid yet.
+    pub royalties_due: BTreeMap<ActorId, Price>,
+    /// Number of blocks after which a pending purchase is cancelled by the store itself.
+    pub tx_expiry: Option<u32>,
+}
+
+#[derive(Encode, Decode, Clone, Copy, TypeInfo, Debug, PartialEq, Eq)]
+#[codec(crate = gstd::codec)]
+#[scale_info(crate = gstd::scale_info)]
+pub enum Discount {
+    Percent(u8),
+    Absolute(Price),
+}
+
+impl Discount {
+    pub fn apply(&self, price: Price) -> Price {
+        match self {
+            Discount::Percent(percent) => price - percent_of(price, (*percent).min(100)),
+            Discount::Absolute(amount) => price.saturating_sub(*amount),
+        }
+    }
+}
+
+/// Returns `percent` percent of `amount`, rounded down, without overflowing.
+pub fn percent_of(amount: Price, percent: u8) -> Price {
+    let percent = Price::from(percent);
+    amount / 100 * percent + amount % 100 * percent / 100
+}
+
+/// Discount on a set of attributes until `end_block`, if set.
+#[derive(Encode, Decode, Clone, TypeInfo, Debug, PartialEq, Eq)]
+#[codec(crate = gstd::codec)]
+#[scale_info(crate = gstd::scale_info)]
+pub struct Campaign {
+    pub discount: Discount,
+    pub attributes: BTreeSet<AttributeId>,
+    pub end_block: Option<u32>,
+}
+
+/// Several attributes sold together for one price.
+#[derive(Encode, Decode, Clone, TypeInfo, Debug, PartialEq, Eq)]
+#[codec(crate = gstd::codec)]
+#[scale_info(crate = gstd::scale_info)]
+pub struct Bundle {
+    pub attributes: BTreeSet<AttributeId>,
+    pub price: Price,
+}
+
+#[derive(Encode, Decode, Clone, Copy, TypeInfo, Debug, PartialEq, Eq)]
+#[codec(crate = gstd::codec)]
+#[scale_info(crate = gstd::scale_info)]
+pub struct Receipt {
+    pub price: Price,
+    pub bought_at: u32,
+}
+
+/// Purchase of a tamagotchi that hasn't completed yet.
+#[derive(Encode, Decode, Clone, Copy, TypeInfo, Debug, PartialEq, Eq)]
+#[codec(crate = gstd::codec)]
+#[scale_info(crate = gstd::scale_info)]
+pub struct PendingTx {
+    pub transaction_id: TransactionId,
+    pub attribute_id: AttributeId,
+    /// Price charged for the purchase, fixed when it started.
+    pub price: Price,
+    /// Tamagotchi receiving the attribute of a gift.
+    pub recipient: Option<TamagotchiId>,
+    /// Coupon taken by the purchase, given back if it doesn't complete.
+    pub coupon: Option<(CouponHash, Discount)>,
+    /// Block height at which the purchase started.
+    pub started_at: u32,
+    /// Payment attempts started, only the latest one settles the purchase.
+    pub attempts: u32,
+}
+
+/// Catalog entry of an attribute.
+#[derive(Encode, Decode, Clone, TypeInfo, Debug)]
+#[codec(crate = gstd::codec)]
+#[scale_info(crate = gstd::scale_info)]
+pub struct AttributeInfo {
+    pub attribute_id: AttributeId,
+    pub metadata: AttrMetadata,
+    pub price: Price,
+    pub delisted: bool,
+    pub limits: SaleLimits,
+}
+
+/// Supply and sale window of an attribute, every limit is optional.
+#[derive(Encode, Decode, Clone, Copy, TypeInfo, Debug, Default, PartialEq, Eq)]
+#[codec(crate = gstd::codec)]
+#[scale_info(crate = gstd::scale_info)]
+pub struct SaleLimits {
+    pub max_supply: Option<u32>,
+    /// Sold units, including the ones of purchases still in progress.
+    pub sold: u32,
+    /// First block height at which the attribute can be bought.
+    pub start_block: Option<u32>,
+    /// Last block height at which the attribute can be bought.
+    pub end_block: Option<u32>,
 }
 
 #[derive(Encode, Decode, Clone, TypeInfo, Debug)]
@@ -42,6 +176,34 @@ pub struct AttrMetadata {
     pub title: String,
     pub description: String,
     pub media: String,
+    /// Slot the attribute is worn in, `None` if it can't be equipped.
+    pub slot: Option<Slot>,
+    pub effects: Option<AttrEffects>,
+}
+
+/// Changes an equipped attribute makes to the tamagotchi's stats.
+#[derive(Encode, Decode, Clone, Copy, TypeInfo, Debug, Default, PartialEq, Eq)]
+#[codec(crate = gstd::codec)]
+#[scale_info(crate = gstd::scale_info)]
+pub struct AttrEffects {
+    /// Change of the hunger decay rate in percent, e.g. `-20`.
+    pub hunger_decay_percent: i32,
+    pub boredom_decay_percent: i32,
+    pub energy_decay_percent: i32,
+    /// Extra points added by each feeding, e.g. `500`.
+    pub fill_per_feed: i32,
+    pub fill_per_entertainment: i32,
+    pub fill_per_sleep: i32,
+}
+
+#[derive(Encode, Decode, Clone, Copy, TypeInfo, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[codec(crate = gstd::codec)]
+#[scale_info(crate = gstd::scale_info)]
+pub enum Slot {
+    Hat,
+    Accessory,
+    Background,
+    Body,
 }
 
 #[derive(Encode, Decode, TypeInfo, Debug)]
@@ -53,28 +215,306 @@ pub enum StoreAction {
         attr_metadata: AttrMetadata,
         price: Price,
     },
+    UpdatePrice {
+        attribute_id: AttributeId,
+        price: Price,
+    },
+    UpdateMetadata {
+        attribute_id: AttributeId,
+        attr_metadata: AttrMetadata,
+    },
+    SetSaleLimits {
+        attribute_id: AttributeId,
+        max_supply: Option<u32>,
+        start_block: Option<u32>,
+        end_block: Option<u32>,
+    },
+    DelistAttribute {
+        attribute_id: AttributeId,
+    },
+    RelistAttribute {
+        attribute_id: AttributeId,
+    },
+    CreateCampaign {
+        campaign_id: CampaignId,
+        campaign: Campaign,
+    },
+    EndCampaign {
+        campaign_id: CampaignId,
+    },
+    AddCoupon {
+        coupon_hash: CouponHash,
+        discount: Discount,
+    },
+    RemoveCoupon {
+        coupon_hash: CouponHash,
+    },
+    CreateBundle {
+        bundle_id: BundleId,
+        bundle: Bundle,
+    },
+    RemoveBundle {
+        bundle_id: BundleId,
+    },
     BuyAttribute {
         attribute_id: AttributeId,
+        coupon: Option<String>,
+    },
+    /// Buys an attribute for another tamagotchi.
+    BuyAttributeFor {
+        attribute_id: AttributeId,
+        recipient: TamagotchiId,
+        coupon: Option<String>,
+    },
+    TransferAttribute {
+        to: TamagotchiId,
+        attribute_id: AttributeId,
+    },
+    BuyBundle {
+        bundle_id: BundleId,
     },
     GetAttributes {
         tamagotchi_id: TamagotchiId,
     },
+    GetAttributeMetadata {
+        attribute_id: AttributeId,
+    },
+    GetAttribute {
+        attribute_id: AttributeId,
+    },
+    /// Attributes ordered by id, with the price of the attribute before discounts in the range.
+    GetCatalog {
+        offset: u32,
+        limit: u32,
+        min_price: Option<Price>,
+        max_price: Option<Price>,
+        include_delisted: bool,
+    },
+    GetOwnerAttributes {
+        tamagotchi_id: TamagotchiId,
+        offset: u32,
+        limit: u32,
+    },
     SetFtContractId {
         ft_contract_id: ActorId,
     },
     RemoveTx {
         tamagotchi_id: TamagotchiId,
     },
+    /// Completes the pending purchase of the sender.
+    RetryTx,
+    /// Sent by the store to itself when a pending purchase expires.
+    ExpireTx {
+        tamagotchi_id: TamagotchiId,
+        transaction_id: TransactionId,
+    },
+    SetTxExpiry {
+        blocks: Option<u32>,
+    },
+    GetPendingTx {
+        tamagotchi_id: TamagotchiId,
+    },
+    ProposeAdmin {
+        new_admin: ActorId,
+    },
+    AcceptAdmin,
+    AddOperator {
+        operator: ActorId,
+    },
+    RemoveOperator {
+        operator: ActorId,
+    },
+    AddMarketplace {
+        marketplace: ActorId,
+    },
+    RemoveMarketplace {
+        marketplace: ActorId,
+    },
+    /// Moves a sold attribute, `royalty` is the part of the price the marketplace pays the store.
+    MarketplaceTransfer {
+        from: TamagotchiId,
+        to: TamagotchiId,
+        attribute_id: AttributeId,
+        royalty: Price,
+    },
+    /// Sent by a marketplace once it has paid the store `royalty` of its transfers.
+    ConfirmRoyalty {
+        royalty: Price,
+    },
+    SetTreasury {
+        treasury: ActorId,
+    },
+    Withdraw {
+        to: ActorId,
+        amount: u128,
+    },
+    SetReturnWindow {
+        attribute_id: AttributeId,
+        blocks: Option<u32>,
+    },
+    Refund {
+        tamagotchi_id: TamagotchiId,
+        attribute_id: AttributeId,
+    },
+    ReturnAttribute {
+        attribute_id: AttributeId,
+    },
 }
 
 #[derive(Encode, Decode, TypeInfo)]
 #[codec(crate = gstd::codec)]
 #[scale_info(crate = gstd::scale_info)]
 pub enum StoreEvent {
-    AttributeCreated { attribute_id: AttributeId },
-    AttributeSold { success: bool },
-    Attributes { attributes: BTreeSet<AttributeId> },
-    CompletePrevTx { attribute_id: AttributeId },
-    FtContractIdSet { ft_contract_id: ActorId },
-    TxRemoved { tamagotchi_id: ActorId },
+    AttributeCreated {
+        attribute_id: AttributeId,
+    },
+    PriceUpdated {
+        attribute_id: AttributeId,
+        price: Price,
+    },
+    MetadataUpdated {
+        attribute_id: AttributeId,
+    },
+    SaleLimitsSet {
+        attribute_id: AttributeId,
+        limits: SaleLimits,
+    },
+    AttributeDelisted {
+        attribute_id: AttributeId,
+    },
+    AttributeRelisted {
+        attribute_id: AttributeId,
+    },
+    CampaignCreated {
+        campaign_id: CampaignId,
+    },
+    CampaignEnded {
+        campaign_id: CampaignId,
+    },
+    CouponAdded {
+        coupon_hash: CouponHash,
+    },
+    CouponRemoved {
+        coupon_hash: CouponHash,
+    },
+    InvalidCoupon,
+    BundleCreated {
+        bundle_id: BundleId,
+    },
+    BundleRemoved {
+        bundle_id: BundleId,
+    },
+    AttributeSold {
+        success: bool,
+    },
+    BundleSold {
+        bundle_id: BundleId,
+        attributes: BTreeSet<AttributeId>,
+        success: bool,
+    },
+    CompletePrevBundleTx {
+        bundle_id: BundleId,
+    },
+    SoldOut {
+        attribute_id: AttributeId,
+    },
+    SaleClosed {
+        attribute_id: AttributeId,
+    },
+    Attributes {
+        attributes: BTreeSet<AttributeId>,
+    },
+    AttributeMetadata {
+        attribute_id: AttributeId,
+        metadata: AttrMetadata,
+    },
+    Attribute {
+        attribute_id: AttributeId,
+        info: Option<AttributeInfo>,
+    },
+    /// A page of the query, `total` counts every match.
+    Catalog {
+        attributes: Vec<AttributeInfo>,
+        total: u32,
+    },
+    OwnerAttributes {
+        tamagotchi_id: TamagotchiId,
+        attributes: Vec<AttributeId>,
+        total: u32,
+    },
+    CompletePrevTx {
+        attribute_id: AttributeId,
+        /// Set if the previous purchase is a gift.
+        recipient: Option<TamagotchiId>,
+    },
+    AttributeTransferred {
+        from: TamagotchiId,
+        to: TamagotchiId,
+        attribute_id: AttributeId,
+    },
+    RoyaltyReceived {
+        marketplace: ActorId,
+        royalty: Price,
+    },
+    FtContractIdSet {
+        ft_contract_id: ActorId,
+    },
+    TxRemoved {
+        tamagotchi_id: ActorId,
+    },
+    TxRetried {
+        attribute_id: AttributeId,
+        recipient: Option<TamagotchiId>,
+        success: bool,
+    },
+    NoPendingTx,
+    TxExpirySet {
+        blocks: Option<u32>,
+    },
+    PendingTx {
+        tamagotchi_id: TamagotchiId,
+        transaction: Option<PendingTx>,
+    },
+    AdminProposed {
+        new_admin: ActorId,
+    },
+    AdminChanged {
+        admin: ActorId,
+    },
+    OperatorAdded {
+        operator: ActorId,
+    },
+    OperatorRemoved {
+        operator: ActorId,
+    },
+    MarketplaceAdded {
+        marketplace: ActorId,
+    },
+    MarketplaceRemoved {
+        marketplace: ActorId,
+    },
+    TreasurySet {
+        treasury: ActorId,
+    },
+    Withdrawal {
+        to: ActorId,
+        amount: u128,
+        success: bool,
+    },
+    ReturnWindowSet {
+        attribute_id: AttributeId,
+        blocks: Option<u32>,
+    },
+    Refunded {
+        tamagotchi_id: TamagotchiId,
+        attribute_id: AttributeId,
+        success: bool,
+    },
+    CompletePrevRefund {
+        tamagotchi_id: TamagotchiId,
+        attribute_id: AttributeId,
+    },
+    ReturnWindowClosed {
+        attribute_id: AttributeId,
+    },
 }
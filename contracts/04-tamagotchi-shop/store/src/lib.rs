@@ -8,7 +8,9 @@ use gstd::{
 };
 use sharded_fungible_token_io::{FTokenAction, FTokenEvent, LogicAction};
 use store_io::{
-    AttrMetadata, AttributeId, Price, StoreAction, StoreEvent, TamagotchiId, TransactionId,
+    coupon_hash, AttrMetadata, AttributeId, AttributeInfo, Bundle, BundleId, Campaign, CampaignId,
+    CouponHash, Discount, PendingTx, Price, Receipt, SaleLimits, StoreAction, StoreEvent,
+    TamagotchiId, TransactionId, MAX_PAGE_SIZE,
 };
 
 static mut STORE: Option<AttributeStore> = None;
@@ -20,17 +22,129 @@ struct AttributeStore {
     attributes: BTreeMap<AttributeId, (AttrMetadata, Price)>,
     owners: BTreeMap<TamagotchiId, BTreeSet<AttributeId>>,
     transaction_id: TransactionId,
-    transactions: BTreeMap<TamagotchiId, (TransactionId, AttributeId)>,
+    transactions: BTreeMap<TamagotchiId, PendingTx>,
+    delisted: BTreeSet<AttributeId>,
+    sales: BTreeMap<AttributeId, SaleLimits>,
+    pending_admin: Option<ActorId>,
+    operators: BTreeSet<ActorId>,
+    treasury: Option<ActorId>,
+    revenue: u128,
+    withdrawn: u128,
+    receipts: BTreeMap<(TamagotchiId, AttributeId), Receipt>,
+    return_windows: BTreeMap<AttributeId, u32>,
+    refund_transactions: BTreeMap<TamagotchiId, (TransactionId, AttributeId)>,
+    refunded: u128,
+    campaigns: BTreeMap<CampaignId, Campaign>,
+    coupons: BTreeMap<CouponHash, Discount>,
+    bundles: BTreeMap<BundleId, Bundle>,
+    bundle_transactions: BTreeMap<TamagotchiId, (TransactionId, BundleId, Bundle)>,
+    marketplaces: BTreeSet<ActorId>,
+    royalties_due: BTreeMap<ActorId, Price>,
+    tx_expiry: Option<u32>,
 }
 
 impl AttributeStore {
+    /// The admin is always an operator.
+    fn is_operator(&self, account: &ActorId) -> bool {
+        *account == self.admin || self.operators.contains(account)
+    }
+
+    fn propose_admin(&mut self, new_admin: &ActorId) {
+        assert_eq!(
+            msg::source(),
+            self.admin,
+            "Only admin can propose a new admin"
+        );
+        self.pending_admin = Some(*new_admin);
+        msg::reply(
+            StoreEvent::AdminProposed {
+                new_admin: *new_admin,
+            },
+            0,
+        )
+        .expect("Error in sending a reply `StoreEvent::AdminProposed`");
+    }
+
+    fn accept_admin(&mut self) {
+        assert_eq!(
+            self.pending_admin,
+            Some(msg::source()),
+            "Only the proposed admin can accept the role"
+        );
+        self.admin = msg::source();
+        self.pending_admin = None;
+        msg::reply(StoreEvent::AdminChanged { admin: self.admin }, 0)
+            .expect("Error in sending a reply `StoreEvent::AdminChanged`");
+    }
+
+    fn add_operator(&mut self, operator: &ActorId) {
+        assert_eq!(msg::source(), self.admin, "Only admin can add operators");
+        self.operators.insert(*operator);
+        msg::reply(
+            StoreEvent::OperatorAdded {
+                operator: *operator,
+            },
+            0,
+        )
+        .expect("Error in sending a reply `StoreEvent::OperatorAdded`");
+    }
+
+    fn remove_operator(&mut self, operator: &ActorId) {
+        assert_eq!(msg::source(), self.admin, "Only admin can remove operators");
+        assert!(
+            self.operators.remove(operator),
+            "Account is not an operator"
+        );
+        msg::reply(
+            StoreEvent::OperatorRemoved {
+                operator: *operator,
+            },
+            0,
+        )
+        .expect("Error in sending a reply `StoreEvent::OperatorRemoved`");
+    }
+
+    fn add_marketplace(&mut self, marketplace: &ActorId) {
+        assert_eq!(msg::source(), self.admin, "Only admin can add marketplaces");
+        self.marketplaces.insert(*marketplace);
+        msg::reply(
+            StoreEvent::MarketplaceAdded {
+                marketplace: *marketplace,
+            },
+            0,
+        )
+        .expect("Error in sending a reply `StoreEvent::MarketplaceAdded`");
+    }
+
+    fn remove_marketplace(&mut self, marketplace: &ActorId) {
+        assert_eq!(
+            msg::source(),
+            self.admin,
+            "Only admin can remove marketplaces"
+        );
+        assert!(
+            self.marketplaces.remove(marketplace),
+            "Account is not a marketplace"
+        );
+        msg::reply(
+            StoreEvent::MarketplaceRemoved {
+                marketplace: *marketplace,
+            },
+            0,
+        )
+        .expect("Error in sending a reply `StoreEvent::MarketplaceRemoved`");
+    }
+
     fn create_attribute(
         &mut self,
         attribute_id: AttributeId,
         metadata: &AttrMetadata,
         price: Price,
     ) {
-        assert_eq!(msg::source(), self.admin, "Only admin can add attributes");
+        assert!(
+            self.is_operator(&msg::source()),
+            "Only admin or operators can add attributes"
+        );
 
         if self
             .attributes
@@ -39,71 +153,805 @@ impl AttributeStore {
         {
             panic!("Attribute with that ID already exists");
         }
+        self.sales.insert(attribute_id, SaleLimits::default());
 
         msg::reply(StoreEvent::AttributeCreated { attribute_id }, 0)
             .expect("Error in sending a reply `StoreEvent::AttributeCreated");
     }
-    async fn buy_attribute(&mut self, attribute_id: AttributeId) {
-        let (transaction_id, attribute_id) = if let Some((transaction_id, prev_attribute_id)) =
-            self.transactions.get(&msg::source())
-        {
-            // if `prev_attribute_id` is not equal to `attribute_id` then it means that transaction didn`t completed
+
+    fn update_price(&mut self, attribute_id: AttributeId, price: Price) {
+        assert!(
+            self.is_operator(&msg::source()),
+            "Only admin or operators can update prices"
+        );
+        let (_, attribute_price) = self
+            .attributes
+            .get_mut(&attribute_id)
+            .expect("Can`t get attribute_id");
+        *attribute_price = price;
+
+        msg::reply(
+            StoreEvent::PriceUpdated {
+                attribute_id,
+                price,
+            },
+            0,
+        )
+        .expect("Error in sending a reply `StoreEvent::PriceUpdated`");
+    }
+
+    fn update_metadata(&mut self, attribute_id: AttributeId, metadata: &AttrMetadata) {
+        assert!(
+            self.is_operator(&msg::source()),
+            "Only admin or operators can update metadata"
+        );
+        let (attribute_metadata, _) = self
+            .attributes
+            .get_mut(&attribute_id)
+            .expect("Can`t get attribute_id");
+        *attribute_metadata = metadata.clone();
+
+        msg::reply(StoreEvent::MetadataUpdated { attribute_id }, 0)
+            .expect("Error in sending a reply `StoreEvent::MetadataUpdated`");
+    }
+
+    fn set_sale_limits(
+        &mut self,
+        attribute_id: AttributeId,
+        max_supply: Option<u32>,
+        start_block: Option<u32>,
+        end_block: Option<u32>,
+    ) {
+        assert!(
+            self.is_operator(&msg::source()),
+            "Only admin or operators can set sale limits"
+        );
+        let limits = self
+            .sales
+            .get_mut(&attribute_id)
+            .expect("Can`t get attribute_id");
+        limits.max_supply = max_supply;
+        limits.start_block = start_block;
+        limits.end_block = end_block;
+
+        msg::reply(
+            StoreEvent::SaleLimitsSet {
+                attribute_id,
+                limits: *limits,
+            },
+            0,
+        )
+        .expect("Error in sending a reply `StoreEvent::SaleLimitsSet`");
+    }
+
+    fn delist_attribute(&mut self, attribute_id: AttributeId) {
+        assert!(
+            self.is_operator(&msg::source()),
+            "Only admin or operators can delist attributes"
+        );
+        assert!(
+            self.attributes.contains_key(&attribute_id),
+            "Can`t get attribute_id"
+        );
+        self.delisted.insert(attribute_id);
+
+        msg::reply(StoreEvent::AttributeDelisted { attribute_id }, 0)
+            .expect("Error in sending a reply `StoreEvent::AttributeDelisted`");
+    }
+
+    fn relist_attribute(&mut self, attribute_id: AttributeId) {
+        assert!(
+            self.is_operator(&msg::source()),
+            "Only admin or operators can relist attributes"
+        );
+        assert!(
+            self.delisted.remove(&attribute_id),
+            "Attribute with that ID is not delisted"
+        );
+
+        msg::reply(StoreEvent::AttributeRelisted { attribute_id }, 0)
+            .expect("Error in sending a reply `StoreEvent::AttributeRelisted`");
+    }
+
+    async fn buy_attribute(
+        &mut self,
+        attribute_id: AttributeId,
+        recipient: Option<TamagotchiId>,
+        coupon: Option<String>,
+    ) {
+        // a gift to the buyer itself is an ordinary purchase
+        let recipient = recipient.filter(|recipient| *recipient != msg::source());
+        if let Some(prev) = self.transactions.get(&msg::source()) {
+            // if `prev.attribute_id` is not equal to `attribute_id` then it means that transaction didn`t completed
             // we ask the tamagotchi contract to complete the previous transaction
-            if attribute_id != *prev_attribute_id {
+            if attribute_id != prev.attribute_id || recipient != prev.recipient {
                 msg::reply(
                     StoreEvent::CompletePrevTx {
-                        attribute_id: *prev_attribute_id,
+                        attribute_id: prev.attribute_id,
+                        recipient: prev.recipient,
                     },
                     0,
                 )
                 .expect("Error in sending a reply `StoreEvent::CompletePrevTx`");
                 return;
             }
-            (*transaction_id, *prev_attribute_id)
         } else {
+            // a pending transaction is completed even if its attribute has been delisted since
+            if self.delisted.contains(&attribute_id) {
+                msg::reply(StoreEvent::AttributeSold { success: false }, 0)
+                    .expect("Error in sending a reply `StoreEvent::AttributeSold`");
+                return;
+            }
+            if let Some(recipient) = recipient {
+                if self
+                    .owners
+                    .get(&recipient)
+                    .is_some_and(|attributes| attributes.contains(&attribute_id))
+                {
+                    msg::reply(StoreEvent::AttributeSold { success: false }, 0)
+                        .expect("Error in sending a reply `StoreEvent::AttributeSold`");
+                    return;
+                }
+            }
+            let coupon = match coupon.as_deref().map(coupon_hash) {
+                Some(hash) => match self.coupons.get(&hash) {
+                    Some(discount) => Some((hash, *discount)),
+                    None => {
+                        msg::reply(StoreEvent::InvalidCoupon, 0)
+                            .expect("Error in sending a reply `StoreEvent::InvalidCoupon`");
+                        return;
+                    }
+                },
+                None => None,
+            };
+            if let Err(event) = self.reserve_unit(attribute_id) {
+                msg::reply(event, 0).expect("Error in sending a reply `StoreEvent`");
+                return;
+            }
+
+            // the price is fixed for the whole transaction, so a retry charges the same amount
+            let mut price = self.campaign_price(attribute_id);
+            if let Some((hash, discount)) = coupon {
+                self.coupons.remove(&hash);
+                price = discount.apply(price);
+            }
+
             let current_transaction_id = self.transaction_id;
             self.transaction_id = self.transaction_id.wrapping_add(1);
-            self.transactions
-                .insert(msg::source(), (current_transaction_id, attribute_id));
-            (current_transaction_id, attribute_id)
-        };
+            self.transactions.insert(
+                msg::source(),
+                PendingTx {
+                    transaction_id: current_transaction_id,
+                    attribute_id,
+                    price,
+                    recipient,
+                    coupon,
+                    started_at: exec::block_height(),
+                    attempts: 0,
+                },
+            );
+            if let Some(blocks) = self.tx_expiry {
+                msg::send_delayed(
+                    exec::program_id(),
+                    StoreAction::ExpireTx {
+                        tamagotchi_id: msg::source(),
+                        transaction_id: current_transaction_id,
+                    },
+                    0,
+                    blocks,
+                )
+                .expect("Error in sending a delayed message `StoreAction::ExpireTx`");
+            }
+        }
 
-        let result = self.sell_attribute(transaction_id, attribute_id).await;
-        self.transactions.remove(&msg::source());
+        let Some((_, _, result)) = self.settle(msg::source()).await else {
+            // a later attempt settles the purchase
+            msg::reply(
+                StoreEvent::CompletePrevTx {
+                    attribute_id,
+                    recipient,
+                },
+                0,
+            )
+            .expect("Error in sending a reply `StoreEvent::CompletePrevTx`");
+            return;
+        };
 
         msg::reply(StoreEvent::AttributeSold { success: result }, 0)
             .expect("Error in sending a reply `StoreEvent::AttributeSold`");
     }
 
-    async fn sell_attribute(
+    /// Completes the pending purchase of a tamagotchi with its transaction id and price.
+    ///
+    /// Every call is a new payment attempt with the same transaction id, so the tokens are
+    /// transferred at most once. The attempt is recorded before the transfer and only the
+    /// latest one settles the purchase, an attempt that finds another one still running once
+    /// the transfer replies returns `None`. An attempt that finds the purchase settled by a
+    /// later one reports that outcome.
+    ///
+    /// Returns the attribute, the gift recipient and whether the purchase was paid.
+    async fn settle(
         &mut self,
-        transaction_id: TransactionId,
-        attribute_id: AttributeId,
-    ) -> bool {
+        buyer: TamagotchiId,
+    ) -> Option<(AttributeId, Option<TamagotchiId>, bool)> {
+        let tx = self.transactions.get_mut(&buyer)?;
+        tx.attempts = tx.attempts.wrapping_add(1);
+        let tx = *tx;
+
+        let result = transfer_tokens(
+            tx.transaction_id,
+            &self.ft_contract_id,
+            &buyer,
+            &exec::program_id(),
+            tx.price,
+        )
+        .await
+        .is_ok();
+        match self.transactions.get(&buyer) {
+            Some(pending) if *pending == tx => {}
+            Some(pending) if pending.transaction_id == tx.transaction_id => return None,
+            // a later attempt with the same transaction id has settled the purchase
+            _ => {
+                let sold = self
+                    .owners
+                    .get(&tx.recipient.unwrap_or(buyer))
+                    .is_some_and(|attributes| attributes.contains(&tx.attribute_id));
+                return Some((tx.attribute_id, tx.recipient, sold));
+            }
+        }
+
+        self.transactions.remove(&buyer);
+        if result {
+            self.sell_attribute(&tx, tx.recipient.unwrap_or(buyer));
+        } else {
+            self.give_back(&tx);
+        }
+        Some((tx.attribute_id, tx.recipient, result))
+    }
+
+    /// Lets a tamagotchi complete its pending purchase without knowing the attribute.
+    async fn retry_tx(&mut self) {
+        let event = match self.settle(msg::source()).await {
+            Some((attribute_id, recipient, success)) => StoreEvent::TxRetried {
+                attribute_id,
+                recipient,
+                success,
+            },
+            None => StoreEvent::NoPendingTx,
+        };
+        msg::reply(event, 0).expect("Error in sending a reply `StoreEvent`");
+    }
+
+    /// Settles a purchase that is still pending when its expiry message arrives.
+    ///
+    /// The payment is attempted once more with the same transaction id, so tokens already
+    /// taken by an attempt that never replied complete the sale, and the purchase is only
+    /// cancelled if this attempt fails. An attempt still in flight is superseded and reports
+    /// the outcome of this one once it replies.
+    async fn expire_tx(&mut self, tamagotchi_id: TamagotchiId, transaction_id: TransactionId) {
+        assert_eq!(
+            msg::source(),
+            exec::program_id(),
+            "Only the store can expire transactions"
+        );
+        if self
+            .transactions
+            .get(&tamagotchi_id)
+            .is_some_and(|tx| tx.transaction_id == transaction_id)
+        {
+            self.settle(tamagotchi_id).await;
+        }
+    }
+
+    fn set_tx_expiry(&mut self, blocks: Option<u32>) {
+        assert_eq!(
+            msg::source(),
+            self.admin,
+            "Only admin can set the transaction expiry"
+        );
+        assert!(blocks != Some(0), "Expiry must be at least one block");
+        self.tx_expiry = blocks;
+
+        msg::reply(StoreEvent::TxExpirySet { blocks }, 0)
+            .expect("Error in sending a reply `StoreEvent::TxExpirySet`");
+    }
+
+    fn get_pending_tx(&self, tamagotchi_id: &TamagotchiId) {
+        msg::reply(
+            StoreEvent::PendingTx {
+                tamagotchi_id: *tamagotchi_id,
+                transaction: self.transactions.get(tamagotchi_id).copied(),
+            },
+            0,
+        )
+        .expect("Error in sending a reply `StoreEvent::PendingTx`");
+    }
+
+    /// Returns the attribute price with the best discount of the running campaigns.
+    fn campaign_price(&self, attribute_id: AttributeId) -> Price {
         let (_, price) = self
             .attributes
             .get(&attribute_id)
             .expect("Can`t get attribute_id");
+        let current_block = exec::block_height();
+
+        self.campaigns
+            .values()
+            .filter(|campaign| {
+                campaign.attributes.contains(&attribute_id)
+                    && campaign.end_block.map_or(true, |end| current_block <= end)
+            })
+            .map(|campaign| campaign.discount.apply(*price))
+            .min()
+            .unwrap_or(*price)
+    }
+
+    /// Takes a unit of the attribute for a new purchase if it's on sale.
+    ///
+    /// The unit is taken before the tokens are transferred, so concurrent purchases can't
+    /// exceed the supply, and a purchase being completed isn't checked again.
+    fn reserve_unit(&mut self, attribute_id: AttributeId) -> Result<(), StoreEvent> {
+        let limits = self
+            .sales
+            .get_mut(&attribute_id)
+            .expect("Can`t get attribute_id");
+        let current_block = exec::block_height();
+
+        if limits
+            .start_block
+            .is_some_and(|start| current_block < start)
+            || limits.end_block.is_some_and(|end| current_block > end)
+        {
+            return Err(StoreEvent::SaleClosed { attribute_id });
+        }
+        if limits.max_supply.is_some_and(|max| limits.sold >= max) {
+            return Err(StoreEvent::SoldOut { attribute_id });
+        }
+
+        limits.sold += 1;
+        Ok(())
+    }
+
+    fn release_unit(&mut self, attribute_id: AttributeId) {
+        if let Some(limits) = self.sales.get_mut(&attribute_id) {
+            limits.sold = limits.sold.saturating_sub(1);
+        }
+    }
+
+    /// Books a paid purchase and gives its attribute to the recipient.
+    fn sell_attribute(&mut self, tx: &PendingTx, recipient: TamagotchiId) {
+        self.revenue = self.revenue.saturating_add(tx.price);
+        // a gift is returned by its recipient
+        self.receipts.insert(
+            (recipient, tx.attribute_id),
+            Receipt {
+                price: tx.price,
+                bought_at: exec::block_height(),
+            },
+        );
+        self.add_to_owner(recipient, tx.attribute_id);
+    }
+
+    /// Puts the unit and the coupon taken by a purchase that didn't complete back on sale.
+    fn give_back(&mut self, tx: &PendingTx) {
+        self.release_unit(tx.attribute_id);
+        if let Some((hash, discount)) = tx.coupon {
+            self.coupons.insert(hash, discount);
+        }
+    }
+
+    fn add_to_owner(&mut self, tamagotchi_id: TamagotchiId, attribute_id: AttributeId) {
+        self.owners
+            .entry(tamagotchi_id)
+            .and_modify(|attributes| {
+                attributes.insert(attribute_id);
+            })
+            .or_insert_with(|| [attribute_id].into());
+    }
+
+    /// Moves an attribute to another tamagotchi.
+    ///
+    /// The receipt isn't moved, so a transferred attribute can't be returned.
+    fn transfer_attribute(&mut self, to: &TamagotchiId, attribute_id: AttributeId) {
+        self.move_attribute(msg::source(), *to, attribute_id);
+    }
+
+    /// Moves an attribute sold on a trusted marketplace and books the royalty as revenue.
+    fn marketplace_transfer(
+        &mut self,
+        from: &TamagotchiId,
+        to: &TamagotchiId,
+        attribute_id: AttributeId,
+        royalty: Price,
+    ) {
+        assert!(
+            self.marketplaces.contains(&msg::source()),
+            "Only trusted marketplaces can move attributes"
+        );
+        self.move_attribute(*from, *to, attribute_id);
+        // the royalty is booked once the marketplace has paid it
+        if royalty > 0 {
+            let due = self.royalties_due.entry(msg::source()).or_default();
+            *due = due.saturating_add(royalty);
+        }
+    }
+
+    fn confirm_royalty(&mut self, royalty: Price) {
+        let marketplace = msg::source();
+        assert!(
+            self.marketplaces.contains(&marketplace),
+            "Only trusted marketplaces can pay royalties"
+        );
+        let due = self
+            .royalties_due
+            .get_mut(&marketplace)
+            .filter(|due| **due >= royalty)
+            .expect("The royalty isn`t due");
+        *due -= royalty;
+        if *due == 0 {
+            self.royalties_due.remove(&marketplace);
+        }
+        self.revenue = self.revenue.saturating_add(royalty);
+
+        msg::reply(
+            StoreEvent::RoyaltyReceived {
+                marketplace,
+                royalty,
+            },
+            0,
+        )
+        .expect("Error in sending a reply `StoreEvent::RoyaltyReceived`");
+    }
+
+    fn move_attribute(&mut self, from: TamagotchiId, to: TamagotchiId, attribute_id: AttributeId) {
+        let attributes = self
+            .owners
+            .get_mut(&from)
+            .expect("That tamagotchi doesn`t own any attributes");
+        assert!(
+            attributes.remove(&attribute_id),
+            "That tamagotchi doesn`t own the attribute"
+        );
+        if attributes.is_empty() {
+            self.owners.remove(&from);
+        }
+        assert!(
+            !self
+                .owners
+                .get(&to)
+                .is_some_and(|attributes| attributes.contains(&attribute_id)),
+            "The recipient already owns the attribute"
+        );
+        self.receipts.remove(&(from, attribute_id));
+        self.add_to_owner(to, attribute_id);
+
+        msg::reply(
+            StoreEvent::AttributeTransferred {
+                from,
+                to,
+                attribute_id,
+            },
+            0,
+        )
+        .expect("Error in sending a reply `StoreEvent::AttributeTransferred`");
+    }
+
+    /// Sells all attributes of a bundle for its price in a single token transfer.
+    ///
+    /// Bundled attributes have no receipts, so they can't be returned one by one.
+    async fn buy_bundle(&mut self, bundle_id: BundleId) {
+        let pending = if let Some(pending) = self.bundle_transactions.get(&msg::source()) {
+            let (_, prev_bundle_id, _) = pending;
+            if bundle_id != *prev_bundle_id {
+                msg::reply(
+                    StoreEvent::CompletePrevBundleTx {
+                        bundle_id: *prev_bundle_id,
+                    },
+                    0,
+                )
+                .expect("Error in sending a reply `StoreEvent::CompletePrevBundleTx`");
+                return;
+            }
+            // a retry pays the price and gets the attributes the purchase started with
+            pending.clone()
+        } else {
+            let bundle = self
+                .bundles
+                .get(&bundle_id)
+                .cloned()
+                .expect("Can`t get bundle_id");
+            let attributes: Vec<AttributeId> = bundle.attributes.iter().copied().collect();
+            if attributes
+                .iter()
+                .any(|attribute_id| self.delisted.contains(attribute_id))
+            {
+                msg::reply(
+                    StoreEvent::BundleSold {
+                        bundle_id,
+                        attributes: bundle.attributes.clone(),
+                        success: false,
+                    },
+                    0,
+                )
+                .expect("Error in sending a reply `StoreEvent::BundleSold`");
+                return;
+            }
+            for (reserved, attribute_id) in attributes.iter().enumerate() {
+                if let Err(event) = self.reserve_unit(*attribute_id) {
+                    for attribute_id in &attributes[..reserved] {
+                        self.release_unit(*attribute_id);
+                    }
+                    msg::reply(event, 0).expect("Error in sending a reply `StoreEvent`");
+                    return;
+                }
+            }
 
-        if transfer_tokens(
+            let pending = (self.transaction_id, bundle_id, bundle);
+            self.transaction_id = self.transaction_id.wrapping_add(1);
+            self.bundle_transactions
+                .insert(msg::source(), pending.clone());
+            pending
+        };
+
+        let (transaction_id, _, Bundle { attributes, price }) = pending.clone();
+        let success = transfer_tokens(
             transaction_id,
             &self.ft_contract_id,
             &msg::source(),
             &exec::program_id(),
-            *price,
+            price,
         )
         .await
-        .is_ok()
+        .is_ok();
+        if self.bundle_transactions.get(&msg::source()) != Some(&pending) {
+            // the admin removed the purchase and put its units back on sale
+            msg::reply(
+                StoreEvent::BundleSold {
+                    bundle_id,
+                    attributes,
+                    success: false,
+                },
+                0,
+            )
+            .expect("Error in sending a reply `StoreEvent::BundleSold`");
+            return;
+        }
+        self.bundle_transactions.remove(&msg::source());
+
+        for attribute_id in &attributes {
+            if success {
+                self.add_to_owner(msg::source(), *attribute_id);
+            } else {
+                self.release_unit(*attribute_id);
+            }
+        }
+        if success {
+            self.revenue = self.revenue.saturating_add(price);
+        }
+
+        msg::reply(
+            StoreEvent::BundleSold {
+                bundle_id,
+                attributes,
+                success,
+            },
+            0,
+        )
+        .expect("Error in sending a reply `StoreEvent::BundleSold`");
+    }
+
+    fn create_campaign(&mut self, campaign_id: CampaignId, campaign: &Campaign) {
+        assert!(
+            self.is_operator(&msg::source()),
+            "Only admin or operators can create campaigns"
+        );
+        assert!(
+            campaign
+                .attributes
+                .iter()
+                .all(|attribute_id| self.attributes.contains_key(attribute_id)),
+            "Can`t get attribute_id"
+        );
+        if self
+            .campaigns
+            .insert(campaign_id, campaign.clone())
+            .is_some()
         {
-            self.owners
-                .entry(msg::source())
-                .and_modify(|attributes| {
-                    attributes.insert(attribute_id);
-                })
-                .or_insert_with(|| [attribute_id].into());
-            return true;
+            panic!("Campaign with that ID already exists");
+        }
+
+        msg::reply(StoreEvent::CampaignCreated { campaign_id }, 0)
+            .expect("Error in sending a reply `StoreEvent::CampaignCreated`");
+    }
+
+    fn end_campaign(&mut self, campaign_id: CampaignId) {
+        assert!(
+            self.is_operator(&msg::source()),
+            "Only admin or operators can end campaigns"
+        );
+        self.campaigns
+            .remove(&campaign_id)
+            .expect("Can`t get campaign_id");
+
+        msg::reply(StoreEvent::CampaignEnded { campaign_id }, 0)
+            .expect("Error in sending a reply `StoreEvent::CampaignEnded`");
+    }
+
+    fn add_coupon(&mut self, coupon_hash: CouponHash, discount: Discount) {
+        assert!(
+            self.is_operator(&msg::source()),
+            "Only admin or operators can add coupons"
+        );
+        if self.coupons.insert(coupon_hash, discount).is_some() {
+            panic!("Coupon already exists");
+        }
+
+        msg::reply(StoreEvent::CouponAdded { coupon_hash }, 0)
+            .expect("Error in sending a reply `StoreEvent::CouponAdded`");
+    }
+
+    fn remove_coupon(&mut self, coupon_hash: CouponHash) {
+        assert!(
+            self.is_operator(&msg::source()),
+            "Only admin or operators can remove coupons"
+        );
+        self.coupons.remove(&coupon_hash).expect("Can`t get coupon");
+
+        msg::reply(StoreEvent::CouponRemoved { coupon_hash }, 0)
+            .expect("Error in sending a reply `StoreEvent::CouponRemoved`");
+    }
+
+    fn create_bundle(&mut self, bundle_id: BundleId, bundle: &Bundle) {
+        assert!(
+            self.is_operator(&msg::source()),
+            "Only admin or operators can create bundles"
+        );
+        assert!(
+            !bundle.attributes.is_empty()
+                && bundle
+                    .attributes
+                    .iter()
+                    .all(|attribute_id| self.attributes.contains_key(attribute_id)),
+            "Can`t get attribute_id"
+        );
+        if self.bundles.insert(bundle_id, bundle.clone()).is_some() {
+            panic!("Bundle with that ID already exists");
+        }
+
+        msg::reply(StoreEvent::BundleCreated { bundle_id }, 0)
+            .expect("Error in sending a reply `StoreEvent::BundleCreated`");
+    }
+
+    fn remove_bundle(&mut self, bundle_id: BundleId) {
+        assert!(
+            self.is_operator(&msg::source()),
+            "Only admin or operators can remove bundles"
+        );
+        self.bundles
+            .remove(&bundle_id)
+            .expect("Can`t get bundle_id");
+
+        msg::reply(StoreEvent::BundleRemoved { bundle_id }, 0)
+            .expect("Error in sending a reply `StoreEvent::BundleRemoved`");
+    }
+
+    fn set_return_window(&mut self, attribute_id: AttributeId, blocks: Option<u32>) {
+        assert!(
+            self.is_operator(&msg::source()),
+            "Only admin or operators can set return windows"
+        );
+        assert!(
+            self.attributes.contains_key(&attribute_id),
+            "Can`t get attribute_id"
+        );
+        match blocks {
+            Some(blocks) => self.return_windows.insert(attribute_id, blocks),
+            None => self.return_windows.remove(&attribute_id),
+        };
+
+        msg::reply(
+            StoreEvent::ReturnWindowSet {
+                attribute_id,
+                blocks,
+            },
+            0,
+        )
+        .expect("Error in sending a reply `StoreEvent::ReturnWindowSet`");
+    }
+
+    async fn refund_attribute(&mut self, tamagotchi_id: &TamagotchiId, attribute_id: AttributeId) {
+        assert_eq!(msg::source(), self.admin, "Only admin can refund purchases");
+        let event = self.refund(*tamagotchi_id, attribute_id).await;
+        msg::reply(event, 0).expect("Error in sending a reply `StoreEvent::Refunded`");
+    }
+
+    async fn return_attribute(&mut self, attribute_id: AttributeId) {
+        let tamagotchi_id = msg::source();
+        let completing = self
+            .refund_transactions
+            .get(&tamagotchi_id)
+            .is_some_and(|(_, prev_attribute_id)| *prev_attribute_id == attribute_id);
+
+        // a return that is already in progress is completed even if the window has closed since
+        if !completing {
+            let Receipt { bought_at, .. } = self
+                .receipts
+                .get(&(tamagotchi_id, attribute_id))
+                .expect("Attribute wasn`t bought by that tamagotchi");
+            let in_window = self
+                .return_windows
+                .get(&attribute_id)
+                .is_some_and(|blocks| exec::block_height() <= bought_at.saturating_add(*blocks));
+            if !in_window {
+                msg::reply(StoreEvent::ReturnWindowClosed { attribute_id }, 0)
+                    .expect("Error in sending a reply `StoreEvent::ReturnWindowClosed`");
+                return;
+            }
+        }
+
+        let event = self.refund(tamagotchi_id, attribute_id).await;
+        msg::reply(event, 0).expect("Error in sending a reply `StoreEvent::Refunded`");
+    }
+
+    /// Transfers the price of a purchase back and takes the attribute from the tamagotchi.
+    ///
+    /// Refunds are tracked per tamagotchi like purchases, so a refund that didn't complete
+    /// is retried with the same transaction id.
+    async fn refund(
+        &mut self,
+        tamagotchi_id: TamagotchiId,
+        attribute_id: AttributeId,
+    ) -> StoreEvent {
+        let transaction_id = if let Some((transaction_id, prev_attribute_id)) =
+            self.refund_transactions.get(&tamagotchi_id)
+        {
+            if attribute_id != *prev_attribute_id {
+                return StoreEvent::CompletePrevRefund {
+                    tamagotchi_id,
+                    attribute_id: *prev_attribute_id,
+                };
+            }
+            *transaction_id
+        } else {
+            assert!(
+                self.receipts.contains_key(&(tamagotchi_id, attribute_id)),
+                "Attribute wasn`t bought by that tamagotchi"
+            );
+            let current_transaction_id = self.transaction_id;
+            self.transaction_id = self.transaction_id.wrapping_add(1);
+            self.refund_transactions
+                .insert(tamagotchi_id, (current_transaction_id, attribute_id));
+            current_transaction_id
+        };
+        let Receipt { price, .. } = self.receipts[&(tamagotchi_id, attribute_id)];
+
+        let success = transfer_tokens(
+            transaction_id,
+            &self.ft_contract_id,
+            &exec::program_id(),
+            &tamagotchi_id,
+            price,
+        )
+        .await
+        .is_ok();
+        self.refund_transactions.remove(&tamagotchi_id);
+
+        if success {
+            self.receipts.remove(&(tamagotchi_id, attribute_id));
+            if let Some(attributes) = self.owners.get_mut(&tamagotchi_id) {
+                attributes.remove(&attribute_id);
+            }
+            if let Some(limits) = self.sales.get_mut(&attribute_id) {
+                limits.sold = limits.sold.saturating_sub(1);
+            }
+            self.refunded = self.refunded.saturating_add(price);
+        }
+
+        StoreEvent::Refunded {
+            tamagotchi_id,
+            attribute_id,
+            success,
         }
-        false
     }
 
     fn get_attributes(&self, tmg_id: &TamagotchiId) {
@@ -112,6 +960,99 @@ impl AttributeStore {
             .expect("Error in sending a reply `StoreEvent::Attributes`");
     }
 
+    fn get_attribute_metadata(&self, attribute_id: AttributeId) {
+        let (metadata, _) = self
+            .attributes
+            .get(&attribute_id)
+            .expect("Can`t get attribute_id");
+        msg::reply(
+            StoreEvent::AttributeMetadata {
+                attribute_id,
+                metadata: metadata.clone(),
+            },
+            0,
+        )
+        .expect("Error in sending a reply `StoreEvent::AttributeMetadata`");
+    }
+
+    fn get_attribute(&self, attribute_id: AttributeId) {
+        msg::reply(
+            StoreEvent::Attribute {
+                attribute_id,
+                info: self.attribute_info(attribute_id),
+            },
+            0,
+        )
+        .expect("Error in sending a reply `StoreEvent::Attribute`");
+    }
+
+    fn get_catalog(
+        &self,
+        offset: u32,
+        limit: u32,
+        min_price: Option<Price>,
+        max_price: Option<Price>,
+        include_delisted: bool,
+    ) {
+        let matches: Vec<AttributeId> = self
+            .attributes
+            .iter()
+            .filter(|(attribute_id, (_, price))| {
+                (include_delisted || !self.delisted.contains(*attribute_id))
+                    && min_price.map_or(true, |min| *price >= min)
+                    && max_price.map_or(true, |max| *price <= max)
+            })
+            .map(|(attribute_id, _)| *attribute_id)
+            .collect();
+        let attributes = matches
+            .iter()
+            .skip(offset as usize)
+            .take(limit.min(MAX_PAGE_SIZE) as usize)
+            .filter_map(|attribute_id| self.attribute_info(*attribute_id))
+            .collect();
+
+        msg::reply(
+            StoreEvent::Catalog {
+                attributes,
+                total: matches.len() as u32,
+            },
+            0,
+        )
+        .expect("Error in sending a reply `StoreEvent::Catalog`");
+    }
+
+    fn get_owner_attributes(&self, tamagotchi_id: &TamagotchiId, offset: u32, limit: u32) {
+        let owned = self.owners.get(tamagotchi_id);
+        let attributes = owned
+            .into_iter()
+            .flatten()
+            .skip(offset as usize)
+            .take(limit.min(MAX_PAGE_SIZE) as usize)
+            .copied()
+            .collect();
+
+        msg::reply(
+            StoreEvent::OwnerAttributes {
+                tamagotchi_id: *tamagotchi_id,
+                attributes,
+                total: owned.map_or(0, |owned| owned.len() as u32),
+            },
+            0,
+        )
+        .expect("Error in sending a reply `StoreEvent::OwnerAttributes`");
+    }
+
+    fn attribute_info(&self, attribute_id: AttributeId) -> Option<AttributeInfo> {
+        let (metadata, price) = self.attributes.get(&attribute_id)?;
+        Some(AttributeInfo {
+            attribute_id,
+            metadata: metadata.clone(),
+            price: *price,
+            delisted: self.delisted.contains(&attribute_id),
+            limits: self.sales.get(&attribute_id).copied().unwrap_or_default(),
+        })
+    }
+
     fn set_ft_contract_id(&mut self, ft_contract_id: &ActorId) {
         assert_eq!(
             msg::source(),
@@ -128,13 +1069,73 @@ impl AttributeStore {
         .expect("Error in sending a reply `StoreEvent::FtContractIdSet`");
     }
 
+    fn set_treasury(&mut self, treasury: &ActorId) {
+        assert_eq!(msg::source(), self.admin, "Only admin can set the treasury");
+        self.treasury = Some(*treasury);
+        msg::reply(
+            StoreEvent::TreasurySet {
+                treasury: *treasury,
+            },
+            0,
+        )
+        .expect("Error in sending a reply `StoreEvent::TreasurySet`");
+    }
+
+    async fn withdraw(&mut self, to: &ActorId, amount: u128) {
+        assert_eq!(msg::source(), self.admin, "Only admin can withdraw tokens");
+        assert!(
+            *to == self.admin || Some(*to) == self.treasury,
+            "Tokens can only be withdrawn to the admin or the treasury"
+        );
+        let available = self
+            .revenue
+            .saturating_sub(self.withdrawn)
+            .saturating_sub(self.refunded);
+        assert!(amount <= available, "Can`t withdraw more than the revenue");
+        // booked before the transfer, so a concurrent withdrawal sees the smaller balance
+        self.withdrawn = self.withdrawn.saturating_add(amount);
+
+        let transaction_id = self.transaction_id;
+        self.transaction_id = self.transaction_id.wrapping_add(1);
+
+        let success = transfer_tokens(
+            transaction_id,
+            &self.ft_contract_id,
+            &exec::program_id(),
+            to,
+            amount,
+        )
+        .await
+        .is_ok();
+        if !success {
+            self.withdrawn = self.withdrawn.saturating_sub(amount);
+        }
+
+        msg::reply(
+            StoreEvent::Withdrawal {
+                to: *to,
+                amount,
+                success,
+            },
+            0,
+        )
+        .expect("Error in sending a reply `StoreEvent::Withdrawal`");
+    }
+
     fn remove_tx(&mut self, tmg_id: &TamagotchiId) {
         assert_eq!(
             msg::source(),
             self.admin,
             "Only admin can set remove transactions"
         );
-        self.transactions.remove(tmg_id);
+        if let Some(tx) = self.transactions.remove(tmg_id) {
+            self.give_back(&tx);
+        }
+        if let Some((_, _, bundle)) = self.bundle_transactions.remove(tmg_id) {
+            for attribute_id in bundle.attributes {
+                self.release_unit(attribute_id);
+            }
+        }
         msg::reply(
             StoreEvent::TxRemoved {
                 tamagotchi_id: *tmg_id,
@@ -156,12 +1157,103 @@ async fn main() {
             attr_metadata,
             price,
         } => store.create_attribute(attribute_id, &attr_metadata, price),
-        StoreAction::BuyAttribute { attribute_id } => store.buy_attribute(attribute_id).await,
+        StoreAction::UpdatePrice {
+            attribute_id,
+            price,
+        } => store.update_price(attribute_id, price),
+        StoreAction::UpdateMetadata {
+            attribute_id,
+            attr_metadata,
+        } => store.update_metadata(attribute_id, &attr_metadata),
+        StoreAction::SetSaleLimits {
+            attribute_id,
+            max_supply,
+            start_block,
+            end_block,
+        } => store.set_sale_limits(attribute_id, max_supply, start_block, end_block),
+        StoreAction::DelistAttribute { attribute_id } => store.delist_attribute(attribute_id),
+        StoreAction::RelistAttribute { attribute_id } => store.relist_attribute(attribute_id),
+        StoreAction::CreateCampaign {
+            campaign_id,
+            campaign,
+        } => store.create_campaign(campaign_id, &campaign),
+        StoreAction::EndCampaign { campaign_id } => store.end_campaign(campaign_id),
+        StoreAction::AddCoupon {
+            coupon_hash,
+            discount,
+        } => store.add_coupon(coupon_hash, discount),
+        StoreAction::RemoveCoupon { coupon_hash } => store.remove_coupon(coupon_hash),
+        StoreAction::CreateBundle { bundle_id, bundle } => store.create_bundle(bundle_id, &bundle),
+        StoreAction::RemoveBundle { bundle_id } => store.remove_bundle(bundle_id),
+        StoreAction::BuyAttribute {
+            attribute_id,
+            coupon,
+        } => store.buy_attribute(attribute_id, None, coupon).await,
+        StoreAction::BuyAttributeFor {
+            attribute_id,
+            recipient,
+            coupon,
+        } => {
+            store
+                .buy_attribute(attribute_id, Some(recipient), coupon)
+                .await
+        }
+        StoreAction::TransferAttribute { to, attribute_id } => {
+            store.transfer_attribute(&to, attribute_id)
+        }
+        StoreAction::MarketplaceTransfer {
+            from,
+            to,
+            attribute_id,
+            royalty,
+        } => store.marketplace_transfer(&from, &to, attribute_id, royalty),
+        StoreAction::ConfirmRoyalty { royalty } => store.confirm_royalty(royalty),
+        StoreAction::BuyBundle { bundle_id } => store.buy_bundle(bundle_id).await,
         StoreAction::GetAttributes { tamagotchi_id } => store.get_attributes(&tamagotchi_id),
+        StoreAction::GetAttribute { attribute_id } => store.get_attribute(attribute_id),
+        StoreAction::GetCatalog {
+            offset,
+            limit,
+            min_price,
+            max_price,
+            include_delisted,
+        } => store.get_catalog(offset, limit, min_price, max_price, include_delisted),
+        StoreAction::GetOwnerAttributes {
+            tamagotchi_id,
+            offset,
+            limit,
+        } => store.get_owner_attributes(&tamagotchi_id, offset, limit),
+        StoreAction::GetAttributeMetadata { attribute_id } => {
+            store.get_attribute_metadata(attribute_id)
+        }
         StoreAction::SetFtContractId { ft_contract_id } => {
             store.set_ft_contract_id(&ft_contract_id)
         }
         StoreAction::RemoveTx { tamagotchi_id } => store.remove_tx(&tamagotchi_id),
+        StoreAction::RetryTx => store.retry_tx().await,
+        StoreAction::ExpireTx {
+            tamagotchi_id,
+            transaction_id,
+        } => store.expire_tx(tamagotchi_id, transaction_id).await,
+        StoreAction::SetTxExpiry { blocks } => store.set_tx_expiry(blocks),
+        StoreAction::GetPendingTx { tamagotchi_id } => store.get_pending_tx(&tamagotchi_id),
+        StoreAction::ProposeAdmin { new_admin } => store.propose_admin(&new_admin),
+        StoreAction::AcceptAdmin => store.accept_admin(),
+        StoreAction::AddOperator { operator } => store.add_operator(&operator),
+        StoreAction::RemoveOperator { operator } => store.remove_operator(&operator),
+        StoreAction::AddMarketplace { marketplace } => store.add_marketplace(&marketplace),
+        StoreAction::RemoveMarketplace { marketplace } => store.remove_marketplace(&marketplace),
+        StoreAction::SetTreasury { treasury } => store.set_treasury(&treasury),
+        StoreAction::Withdraw { to, amount } => store.withdraw(&to, amount).await,
+        StoreAction::SetReturnWindow {
+            attribute_id,
+            blocks,
+        } => store.set_return_window(attribute_id, blocks),
+        StoreAction::Refund {
+            tamagotchi_id,
+            attribute_id,
+        } => store.refund_attribute(&tamagotchi_id, attribute_id).await,
+        StoreAction::ReturnAttribute { attribute_id } => store.return_attribute(attribute_id).await,
     }
 }
 
@@ -217,6 +1309,24 @@ extern fn state() {
             owners: store.owners.clone(),
             transaction_id: store.transaction_id,
             transactions: store.transactions.clone(),
+            delisted: store.delisted.clone(),
+            sales: store.sales.clone(),
+            pending_admin: store.pending_admin,
+            operators: store.operators.clone(),
+            treasury: store.treasury,
+            revenue: store.revenue,
+            withdrawn: store.withdrawn,
+            receipts: store.receipts.clone(),
+            return_windows: store.return_windows.clone(),
+            refund_transactions: store.refund_transactions.clone(),
+            refunded: store.refunded,
+            campaigns: store.campaigns.clone(),
+            coupons: store.coupons.clone(),
+            bundles: store.bundles.clone(),
+            bundle_transactions: store.bundle_transactions.clone(),
+            marketplaces: store.marketplaces.clone(),
+            royalties_due: store.royalties_due.clone(),
+            tx_expiry: store.tx_expiry,
         },
         0,
     )
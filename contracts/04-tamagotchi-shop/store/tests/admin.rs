@@ -0,0 +1,158 @@
+use gstd::ActorId;
+use gtest::{Log, Program, System};
+use store_io::{AttrMetadata, AttributeStore, StoreAction, StoreEvent};
+
+const ADMIN: u64 = 2;
+const TAMAGOTCHI: u64 = 3;
+const FT_CONTRACT: u64 = 4;
+
+const ATTRIBUTE: u32 = 1;
+
+fn metadata(title: &str) -> AttrMetadata {
+    AttrMetadata {
+        title: title.to_string(),
+        description: String::new(),
+        media: String::new(),
+        slot: None,
+        effects: None,
+    }
+}
+
+fn init_store(sys: &System) -> Program<'_> {
+    sys.init_logger();
+    let store = Program::current(sys);
+    let res = store.send(ADMIN, ActorId::from(FT_CONTRACT));
+    assert!(!res.main_failed());
+
+    let res = store.send(
+        ADMIN,
+        StoreAction::CreateAttribute {
+            attribute_id: ATTRIBUTE,
+            attr_metadata: metadata("Sword"),
+            price: 100,
+        },
+    );
+    assert!(!res.main_failed());
+    store
+}
+
+fn read_state(store: &Program<'_>) -> AttributeStore {
+    store.read_state(0).expect("Unable to read the store state")
+}
+
+#[test]
+fn admin_updates_attribute() {
+    let sys = System::new();
+    let store = init_store(&sys);
+
+    let res = store.send(
+        ADMIN,
+        StoreAction::UpdatePrice {
+            attribute_id: ATTRIBUTE,
+            price: 250,
+        },
+    );
+    assert!(res.contains(
+        &Log::builder()
+            .dest(ADMIN)
+            .payload(StoreEvent::PriceUpdated {
+                attribute_id: ATTRIBUTE,
+                price: 250,
+            })
+    ));
+
+    let res = store.send(
+        ADMIN,
+        StoreAction::UpdateMetadata {
+            attribute_id: ATTRIBUTE,
+            attr_metadata: metadata("Golden sword"),
+        },
+    );
+    assert!(res.contains(
+        &Log::builder()
+            .dest(ADMIN)
+            .payload(StoreEvent::MetadataUpdated {
+                attribute_id: ATTRIBUTE,
+            })
+    ));
+
+    let (metadata, price) = &read_state(&store).attributes[&ATTRIBUTE];
+    assert_eq!(metadata.title, "Golden sword");
+    assert_eq!(*price, 250);
+}
+
+#[test]
+fn only_admin_manages_attributes() {
+    let sys = System::new();
+    let store = init_store(&sys);
+
+    for action in [
+        StoreAction::UpdatePrice {
+            attribute_id: ATTRIBUTE,
+            price: 0,
+        },
+        StoreAction::UpdateMetadata {
+            attribute_id: ATTRIBUTE,
+            attr_metadata: metadata("Stolen sword"),
+        },
+        StoreAction::DelistAttribute {
+            attribute_id: ATTRIBUTE,
+        },
+    ] {
+        assert!(store.send(TAMAGOTCHI, action).main_failed());
+    }
+
+    let state = read_state(&store);
+    assert_eq!(state.attributes[&ATTRIBUTE].1, 100);
+    assert!(state.delisted.is_empty());
+}
+
+#[test]
+fn delisted_attribute_is_not_sold() {
+    let sys = System::new();
+    let store = init_store(&sys);
+
+    let res = store.send(
+        ADMIN,
+        StoreAction::DelistAttribute {
+            attribute_id: ATTRIBUTE,
+        },
+    );
+    assert!(res.contains(
+        &Log::builder()
+            .dest(ADMIN)
+            .payload(StoreEvent::AttributeDelisted {
+                attribute_id: ATTRIBUTE,
+            })
+    ));
+    assert!(read_state(&store).delisted.contains(&ATTRIBUTE));
+
+    let res = store.send(
+        TAMAGOTCHI,
+        StoreAction::BuyAttribute {
+            attribute_id: ATTRIBUTE,
+            coupon: None,
+        },
+    );
+    assert!(res.contains(
+        &Log::builder()
+            .dest(TAMAGOTCHI)
+            .payload(StoreEvent::AttributeSold { success: false })
+    ));
+    assert!(read_state(&store).transactions.is_empty());
+
+    let res = store.send(
+        ADMIN,
+        StoreAction::RelistAttribute {
+            attribute_id: ATTRIBUTE,
+        },
+    );
+    assert!(res.contains(
+        &Log::builder()
+            .dest(ADMIN)
+            .payload(StoreEvent::AttributeRelisted {
+                attribute_id: ATTRIBUTE,
+            })
+    ));
+    assert!(read_state(&store).delisted.is_empty());
+}
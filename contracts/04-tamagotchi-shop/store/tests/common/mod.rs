@@ -0,0 +1,125 @@
+#![allow(dead_code)]
+
+use gstd::ActorId;
+use gtest::{Log, Program, System};
+use sharded_fungible_token_io::{FTokenAction, FTokenEvent, InitFToken, LogicAction};
+use store_io::{AttrMetadata, AttributeStore, StoreAction, StoreEvent};
+
+pub const FT_MAIN_WASM: &str =
+    "../../target/wasm32-unknown-unknown/debug/sharded_fungible_token.opt.wasm";
+pub const FT_LOGIC_WASM: &str =
+    "../../target/wasm32-unknown-unknown/debug/sharded_fungible_token_logic.opt.wasm";
+pub const FT_STORAGE_WASM: &str =
+    "../../target/wasm32-unknown-unknown/debug/sharded_fungible_token_storage.opt.wasm";
+
+pub const ADMIN: u64 = 2;
+pub const TAMAGOTCHI: u64 = 3;
+/// Account without a program, purchases paid through it never get a reply.
+pub const SILENT_FT: u64 = 9;
+pub const FT_ID: u64 = 10;
+pub const STORE_ID: u64 = 11;
+
+pub const SWORD: u32 = 1;
+pub const PRICE: u128 = 100;
+pub const BALANCE: u128 = 1000;
+
+/// Deploys the token contract, the tamagotchi gets `balance` and lets the store spend it.
+pub fn init_ft(sys: &System, balance: u128) -> Program<'_> {
+    let storage_code_id = sys.submit_code(FT_STORAGE_WASM);
+    let logic_code_id = sys.submit_code(FT_LOGIC_WASM);
+    let ft = Program::from_file_with_id(sys, FT_ID, FT_MAIN_WASM);
+    let res = ft.send(
+        ADMIN,
+        InitFToken {
+            storage_code_hash: storage_code_id.into_bytes().into(),
+            ft_logic_code_hash: logic_code_id.into_bytes().into(),
+        },
+    );
+    assert!(!res.main_failed());
+
+    for (transaction_id, payload) in [
+        LogicAction::Mint {
+            recipient: TAMAGOTCHI.into(),
+            amount: balance,
+        },
+        LogicAction::Approve {
+            approved_account: STORE_ID.into(),
+            amount: balance,
+        },
+    ]
+    .into_iter()
+    .enumerate()
+    {
+        let res = ft.send(
+            TAMAGOTCHI,
+            FTokenAction::Message {
+                transaction_id: transaction_id as u64,
+                payload,
+            },
+        );
+        assert!(res.contains(&Log::builder().dest(TAMAGOTCHI).payload(FTokenEvent::Ok)));
+    }
+    ft
+}
+
+/// Deploys the store selling the sword for `PRICE`, paid with `ft_contract_id`.
+pub fn init_store(sys: &System, ft_contract_id: u64) -> Program<'_> {
+    let store = Program::current_with_id(sys, STORE_ID);
+    let res = store.send(ADMIN, ActorId::from(ft_contract_id));
+    assert!(!res.main_failed());
+    create_attribute(&store, SWORD, "Sword");
+    store
+}
+
+/// Puts an attribute on sale for `PRICE`.
+pub fn create_attribute(store: &Program<'_>, attribute_id: u32, title: &str) {
+    let res = store.send(
+        ADMIN,
+        StoreAction::CreateAttribute {
+            attribute_id,
+            attr_metadata: AttrMetadata {
+                title: String::from(title),
+                description: String::new(),
+                media: String::new(),
+                slot: None,
+                effects: None,
+            },
+            price: PRICE,
+        },
+    );
+    assert!(!res.main_failed());
+}
+
+pub fn buy(store: &Program<'_>, attribute_id: u32, coupon: Option<&str>, event: StoreEvent) {
+    let res = store.send(
+        TAMAGOTCHI,
+        StoreAction::BuyAttribute {
+            attribute_id,
+            coupon: coupon.map(String::from),
+        },
+    );
+    assert!(res.contains(&Log::builder().dest(TAMAGOTCHI).payload(event)));
+}
+
+pub fn use_ft(store: &Program<'_>, ft_contract_id: u64) {
+    let res = store.send(
+        ADMIN,
+        StoreAction::SetFtContractId {
+            ft_contract_id: ft_contract_id.into(),
+        },
+    );
+    assert!(!res.main_failed());
+}
+
+pub fn read_state(store: &Program<'_>) -> AttributeStore {
+    store.read_state(0).expect("Unable to read the store state")
+}
+
+pub fn assert_balance(ft: &Program<'_>, account: u64, balance: u128) {
+    let res = ft.send(ADMIN, FTokenAction::GetBalance(account.into()));
+    assert!(res.contains(
+        &Log::builder()
+            .dest(ADMIN)
+            .payload(FTokenEvent::Balance(balance))
+    ));
+}
@@ -0,0 +1,238 @@
+mod common;
+
+use common::{
+    assert_balance, buy, create_attribute, init_ft, init_store, read_state, use_ft, ADMIN, BALANCE,
+    FT_ID, PRICE, SILENT_FT, SWORD, TAMAGOTCHI,
+};
+use gtest::{Log, Program, System};
+use store_io::{coupon_hash, Bundle, Campaign, Discount, StoreAction, StoreEvent};
+
+const HAT: u32 = 2;
+
+fn init(sys: &System) -> (Program<'_>, Program<'_>) {
+    let ft = init_ft(sys, BALANCE);
+    let store = init_store(sys, FT_ID);
+    create_attribute(&store, HAT, "Hat");
+    (ft, store)
+}
+
+/// Leaves a purchase of a sword and hat bundle waiting for a token contract that doesn't reply.
+fn start_bundle_purchase(store: &Program<'_>) -> Bundle {
+    let bundle = Bundle {
+        attributes: [SWORD, HAT].into(),
+        price: 150,
+    };
+    let res = store.send(
+        ADMIN,
+        StoreAction::CreateBundle {
+            bundle_id: 1,
+            bundle: bundle.clone(),
+        },
+    );
+    assert!(!res.main_failed());
+    let res = store.send(
+        ADMIN,
+        StoreAction::SetSaleLimits {
+            attribute_id: SWORD,
+            max_supply: Some(1),
+            start_block: None,
+            end_block: None,
+        },
+    );
+    assert!(!res.main_failed());
+
+    use_ft(store, SILENT_FT);
+    let res = store.send(TAMAGOTCHI, StoreAction::BuyBundle { bundle_id: 1 });
+    assert!(!res.main_failed());
+    use_ft(store, FT_ID);
+
+    let state = read_state(store);
+    assert_eq!(state.sales[&SWORD].sold, 1);
+    bundle
+}
+
+#[test]
+fn campaign_discounts_its_attributes() {
+    let sys = System::new();
+    sys.init_logger();
+    let (ft, store) = init(&sys);
+
+    let campaign = Campaign {
+        discount: Discount::Percent(20),
+        attributes: [SWORD].into(),
+        end_block: Some(sys.block_height() + 5),
+    };
+    let res = store.send(
+        ADMIN,
+        StoreAction::CreateCampaign {
+            campaign_id: 1,
+            campaign,
+        },
+    );
+    assert!(res.contains(
+        &Log::builder()
+            .dest(ADMIN)
+            .payload(StoreEvent::CampaignCreated { campaign_id: 1 })
+    ));
+
+    buy(
+        &store,
+        SWORD,
+        None,
+        StoreEvent::AttributeSold { success: true },
+    );
+    buy(
+        &store,
+        HAT,
+        None,
+        StoreEvent::AttributeSold { success: true },
+    );
+    assert_balance(&ft, TAMAGOTCHI, BALANCE - 80 - PRICE);
+
+    let res = store.send(TAMAGOTCHI, StoreAction::EndCampaign { campaign_id: 1 });
+    assert!(res.main_failed());
+}
+
+#[test]
+fn coupon_is_used_once() {
+    let sys = System::new();
+    sys.init_logger();
+    let (ft, store) = init(&sys);
+
+    let res = store.send(
+        ADMIN,
+        StoreAction::AddCoupon {
+            coupon_hash: coupon_hash("WELCOME"),
+            discount: Discount::Absolute(30),
+        },
+    );
+    assert!(!res.main_failed());
+
+    buy(&store, SWORD, Some("UNKNOWN"), StoreEvent::InvalidCoupon);
+    buy(
+        &store,
+        SWORD,
+        Some("WELCOME"),
+        StoreEvent::AttributeSold { success: true },
+    );
+    assert_balance(&ft, TAMAGOTCHI, BALANCE - 70);
+
+    buy(&store, HAT, Some("WELCOME"), StoreEvent::InvalidCoupon);
+    let state = read_state(&store);
+    assert!(state.coupons.is_empty());
+    assert_eq!(
+        state.receipts[&(ActorId::from(TAMAGOTCHI), SWORD)].price,
+        70
+    );
+}
+
+#[test]
+fn bundle_is_sold_in_one_transfer() {
+    let sys = System::new();
+    sys.init_logger();
+    let (ft, store) = init(&sys);
+
+    let bundle = Bundle {
+        attributes: [SWORD, HAT].into(),
+        price: 150,
+    };
+    let res = store.send(
+        ADMIN,
+        StoreAction::CreateBundle {
+            bundle_id: 1,
+            bundle,
+        },
+    );
+    assert!(!res.main_failed());
+
+    let res = store.send(TAMAGOTCHI, StoreAction::BuyBundle { bundle_id: 1 });
+    assert!(res.contains(
+        &Log::builder()
+            .dest(TAMAGOTCHI)
+            .payload(StoreEvent::BundleSold {
+                bundle_id: 1,
+                attributes: [SWORD, HAT].into(),
+                success: true,
+            })
+    ));
+    assert_balance(&ft, TAMAGOTCHI, BALANCE - 150);
+
+    let state = read_state(&store);
+    assert_eq!(
+        state.owners[&ActorId::from(TAMAGOTCHI)],
+        [SWORD, HAT].into()
+    );
+    assert_eq!(state.revenue, 150);
+}
+
+#[test]
+fn pending_bundle_purchase_keeps_its_bundle() {
+    let sys = System::new();
+    sys.init_logger();
+    let (ft, store) = init(&sys);
+    let bundle = start_bundle_purchase(&store);
+
+    let res = store.send(ADMIN, StoreAction::RemoveBundle { bundle_id: 1 });
+    assert!(!res.main_failed());
+    let state = read_state(&store);
+    assert_eq!(
+        state.bundle_transactions[&ActorId::from(TAMAGOTCHI)],
+        (0, 1, bundle)
+    );
+
+    // the retry completes the purchase at the price it started with
+    let res = store.send(TAMAGOTCHI, StoreAction::BuyBundle { bundle_id: 1 });
+    assert!(res.contains(
+        &Log::builder()
+            .dest(TAMAGOTCHI)
+            .payload(StoreEvent::BundleSold {
+                bundle_id: 1,
+                attributes: [SWORD, HAT].into(),
+                success: true,
+            })
+    ));
+    assert_balance(&ft, TAMAGOTCHI, BALANCE - 150);
+
+    let state = read_state(&store);
+    assert!(state.bundle_transactions.is_empty());
+    assert_eq!(
+        state.owners[&ActorId::from(TAMAGOTCHI)],
+        [SWORD, HAT].into()
+    );
+    assert_eq!(state.revenue, 150);
+}
+
+#[test]
+fn removed_bundle_purchase_releases_its_units() {
+    let sys = System::new();
+    sys.init_logger();
+    let (_ft, store) = init(&sys);
+    start_bundle_purchase(&store);
+
+    let res = store.send(
+        ADMIN,
+        StoreAction::RemoveTx {
+            tamagotchi_id: TAMAGOTCHI.into(),
+        },
+    );
+    assert!(
+        res.contains(&Log::builder().dest(ADMIN).payload(StoreEvent::TxRemoved {
+            tamagotchi_id: TAMAGOTCHI.into(),
+        }))
+    );
+
+    let state = read_state(&store);
+    assert!(state.bundle_transactions.is_empty());
+    assert_eq!(state.sales[&SWORD].sold, 0);
+    assert!(!state.owners.contains_key(&ActorId::from(TAMAGOTCHI)));
+}
+
+#[test]
+fn percent_discount_of_large_price() {
+    assert_eq!(Discount::Percent(20).apply(1_000), 800);
+    assert_eq!(Discount::Percent(50).apply(199), 100);
+    assert_eq!(
+        Discount::Percent(50).apply(u128::MAX),
+        u128::MAX - u128::MAX / 2
+    );
+}
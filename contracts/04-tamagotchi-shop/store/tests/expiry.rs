@@ -0,0 +1,181 @@
+mod common;
+
+use common::{
+    init_ft, read_state, use_ft, ADMIN, FT_ID, PRICE, SILENT_FT, STORE_ID, SWORD, TAMAGOTCHI,
+};
+use gstd::ActorId;
+use gtest::{Log, Program, System};
+use sharded_fungible_token_io::{FTokenAction, FTokenEvent, LogicAction};
+use store_io::{PendingTx, StoreAction, StoreEvent};
+
+const EXPIRY: u32 = 5;
+
+/// The store has a purchase of the tamagotchi waiting for a token contract that doesn't reply.
+fn init_store(sys: &System, tx_expiry: Option<u32>) -> Program<'_> {
+    sys.init_logger();
+    let store = common::init_store(sys, SILENT_FT);
+    let res = store.send(ADMIN, StoreAction::SetTxExpiry { blocks: tx_expiry });
+    assert!(!res.main_failed());
+
+    let res = store.send(
+        TAMAGOTCHI,
+        StoreAction::BuyAttribute {
+            attribute_id: SWORD,
+            coupon: None,
+        },
+    );
+    assert!(!res.main_failed());
+    store
+}
+
+#[test]
+fn pending_purchase_is_stamped() {
+    let sys = System::new();
+    let store = init_store(&sys, None);
+    let started_at = read_state(&store).transactions[&ActorId::from(TAMAGOTCHI)].started_at;
+    assert!(started_at > 0);
+
+    let res = store.send(
+        ADMIN,
+        StoreAction::GetPendingTx {
+            tamagotchi_id: TAMAGOTCHI.into(),
+        },
+    );
+    assert!(
+        res.contains(&Log::builder().dest(ADMIN).payload(StoreEvent::PendingTx {
+            tamagotchi_id: TAMAGOTCHI.into(),
+            transaction: Some(PendingTx {
+                transaction_id: 0,
+                attribute_id: SWORD,
+                price: PRICE,
+                recipient: None,
+                coupon: None,
+                started_at,
+                attempts: 1,
+            }),
+        }))
+    );
+}
+
+#[test]
+fn tamagotchi_retries_its_purchase() {
+    let sys = System::new();
+    init_ft(&sys, PRICE);
+    let store = init_store(&sys, None);
+    use_ft(&store, FT_ID);
+
+    let res = store.send(TAMAGOTCHI, StoreAction::RetryTx);
+    assert!(res.contains(
+        &Log::builder()
+            .dest(TAMAGOTCHI)
+            .payload(StoreEvent::TxRetried {
+                attribute_id: SWORD,
+                recipient: None,
+                success: true,
+            })
+    ));
+
+    let res = store.send(TAMAGOTCHI, StoreAction::RetryTx);
+    assert!(res.contains(
+        &Log::builder()
+            .dest(TAMAGOTCHI)
+            .payload(StoreEvent::NoPendingTx)
+    ));
+
+    let state = read_state(&store);
+    assert_eq!(state.owners[&ActorId::from(TAMAGOTCHI)], [SWORD].into());
+    assert!(state.transactions.is_empty());
+    assert_eq!(state.revenue, PRICE);
+}
+
+#[test]
+fn expired_purchase_is_completed_by_the_store() {
+    let sys = System::new();
+    init_ft(&sys, PRICE);
+    let store = init_store(&sys, Some(EXPIRY));
+    use_ft(&store, FT_ID);
+    assert!(!read_state(&store).transactions.is_empty());
+
+    // the store pays with the transaction id of the attempt that never replied
+    sys.spend_blocks(EXPIRY);
+    let state = read_state(&store);
+    assert!(state.transactions.is_empty());
+    assert_eq!(state.owners[&ActorId::from(TAMAGOTCHI)], [SWORD].into());
+    assert_eq!(state.revenue, PRICE);
+
+    // the first attempt replying late reports the sale without booking it again
+    let res = sys
+        .get_mailbox(SILENT_FT)
+        .reply(
+            Log::builder().source(STORE_ID).dest(SILENT_FT),
+            FTokenEvent::Ok,
+            0,
+        )
+        .expect("Unable to reply to the first payment attempt");
+    assert!(res.contains(
+        &Log::builder()
+            .dest(TAMAGOTCHI)
+            .payload(StoreEvent::AttributeSold { success: true })
+    ));
+    let state = read_state(&store);
+    assert_eq!(state.owners[&ActorId::from(TAMAGOTCHI)], [SWORD].into());
+    assert_eq!(state.revenue, PRICE);
+}
+
+#[test]
+fn expired_purchase_is_cancelled_if_its_payment_fails() {
+    let sys = System::new();
+    let ft = init_ft(&sys, PRICE);
+    let store = init_store(&sys, Some(EXPIRY));
+    use_ft(&store, FT_ID);
+
+    // the tamagotchi spends its tokens before the purchase expires
+    let res = ft.send(
+        TAMAGOTCHI,
+        FTokenAction::Message {
+            transaction_id: 2,
+            payload: LogicAction::Transfer {
+                sender: TAMAGOTCHI.into(),
+                recipient: ADMIN.into(),
+                amount: PRICE,
+            },
+        },
+    );
+    assert!(res.contains(&Log::builder().dest(TAMAGOTCHI).payload(FTokenEvent::Ok)));
+
+    sys.spend_blocks(EXPIRY);
+    let state = read_state(&store);
+    assert!(state.transactions.is_empty());
+    assert!(!state.owners.contains_key(&ActorId::from(TAMAGOTCHI)));
+    assert_eq!(state.revenue, 0);
+
+    // the tamagotchi isn't asked to complete the cancelled purchase
+    let res = store.send(
+        TAMAGOTCHI,
+        StoreAction::BuyAttribute {
+            attribute_id: SWORD,
+            coupon: None,
+        },
+    );
+    assert!(res.contains(
+        &Log::builder()
+            .dest(TAMAGOTCHI)
+            .payload(StoreEvent::AttributeSold { success: false })
+    ));
+}
+
+#[test]
+fn only_store_expires_purchases() {
+    let sys = System::new();
+    let store = init_store(&sys, None);
+
+    let res = store.send(
+        TAMAGOTCHI,
+        StoreAction::ExpireTx {
+            tamagotchi_id: TAMAGOTCHI.into(),
+            transaction_id: 0,
+        },
+    );
+    assert!(res.main_failed());
+    assert!(!read_state(&store).transactions.is_empty());
+}
@@ -0,0 +1,150 @@
+use gstd::ActorId;
+use gtest::{Log, Program, System};
+use store_io::{AttrMetadata, AttributeInfo, SaleLimits, StoreAction, StoreEvent};
+
+const ADMIN: u64 = 2;
+const USER: u64 = 3;
+const FT_CONTRACT: u64 = 4;
+
+const PRICES: [u128; 4] = [100, 200, 300, 400];
+
+fn metadata(attribute_id: u32) -> AttrMetadata {
+    AttrMetadata {
+        title: format!("Attribute {attribute_id}"),
+        description: String::new(),
+        media: String::new(),
+        slot: None,
+        effects: None,
+    }
+}
+
+fn info(attribute_id: u32, delisted: bool) -> AttributeInfo {
+    AttributeInfo {
+        attribute_id,
+        metadata: metadata(attribute_id),
+        price: PRICES[attribute_id as usize - 1],
+        delisted,
+        limits: SaleLimits::default(),
+    }
+}
+
+/// Attributes 1 to 4 cost 100 to 400, attribute 2 is delisted.
+fn init_store(sys: &System) -> Program<'_> {
+    sys.init_logger();
+    let store = Program::current(sys);
+    let res = store.send(ADMIN, ActorId::from(FT_CONTRACT));
+    assert!(!res.main_failed());
+
+    for (index, price) in PRICES.into_iter().enumerate() {
+        let attribute_id = index as u32 + 1;
+        let res = store.send(
+            ADMIN,
+            StoreAction::CreateAttribute {
+                attribute_id,
+                attr_metadata: metadata(attribute_id),
+                price,
+            },
+        );
+        assert!(!res.main_failed());
+    }
+    let res = store.send(ADMIN, StoreAction::DelistAttribute { attribute_id: 2 });
+    assert!(!res.main_failed());
+    store
+}
+
+fn assert_reply(store: &Program<'_>, action: StoreAction, event: StoreEvent) {
+    let res = store.send(USER, action);
+    assert!(res.contains(&Log::builder().dest(USER).payload(event)));
+}
+
+#[test]
+fn catalog_is_paginated() {
+    let sys = System::new();
+    let store = init_store(&sys);
+
+    assert_reply(
+        &store,
+        StoreAction::GetCatalog {
+            offset: 0,
+            limit: 2,
+            min_price: None,
+            max_price: None,
+            include_delisted: false,
+        },
+        StoreEvent::Catalog {
+            attributes: vec![info(1, false), info(3, false)],
+            total: 3,
+        },
+    );
+    assert_reply(
+        &store,
+        StoreAction::GetCatalog {
+            offset: 2,
+            limit: 2,
+            min_price: None,
+            max_price: None,
+            include_delisted: false,
+        },
+        StoreEvent::Catalog {
+            attributes: vec![info(4, false)],
+            total: 3,
+        },
+    );
+}
+
+#[test]
+fn catalog_is_filtered_by_price() {
+    let sys = System::new();
+    let store = init_store(&sys);
+
+    assert_reply(
+        &store,
+        StoreAction::GetCatalog {
+            offset: 0,
+            limit: 10,
+            min_price: Some(200),
+            max_price: Some(300),
+            include_delisted: true,
+        },
+        StoreEvent::Catalog {
+            attributes: vec![info(2, true), info(3, false)],
+            total: 2,
+        },
+    );
+}
+
+#[test]
+fn single_attribute_lookup() {
+    let sys = System::new();
+    let store = init_store(&sys);
+
+    assert_reply(
+        &store,
+        StoreAction::GetAttribute { attribute_id: 2 },
+        StoreEvent::Attribute {
+            attribute_id: 2,
+            info: Some(info(2, true)),
+        },
+    );
+    assert_reply(
+        &store,
+        StoreAction::GetAttribute { attribute_id: 5 },
+        StoreEvent::Attribute {
+            attribute_id: 5,
+            info: None,
+        },
+    );
+    assert_reply(
+        &store,
+        StoreAction::GetOwnerAttributes {
+            tamagotchi_id: USER.into(),
+            offset: 0,
+            limit: 10,
+        },
+        StoreEvent::OwnerAttributes {
+            tamagotchi_id: USER.into(),
+            attributes: Vec::new(),
+            total: 0,
+        },
+    );
+}
@@ -0,0 +1,128 @@
+mod common;
+
+use common::{
+    assert_balance, buy, init_ft, init_store, read_state, ADMIN, FT_ID, PRICE, SWORD, TAMAGOTCHI,
+};
+use gtest::{Log, Program, System};
+use store_io::{StoreAction, StoreEvent};
+
+const RETURN_WINDOW: u32 = 10;
+
+/// The tamagotchi has bought the sword with all its tokens.
+fn init(sys: &System) -> (Program<'_>, Program<'_>) {
+    let ft = init_ft(sys, PRICE);
+    let store = init_store(sys, FT_ID);
+    buy(
+        &store,
+        SWORD,
+        None,
+        StoreEvent::AttributeSold { success: true },
+    );
+    (ft, store)
+}
+
+fn set_return_window(store: &Program<'_>, blocks: Option<u32>) {
+    let res = store.send(
+        ADMIN,
+        StoreAction::SetReturnWindow {
+            attribute_id: SWORD,
+            blocks,
+        },
+    );
+    assert!(!res.main_failed());
+}
+
+fn return_attribute(store: &Program<'_>, event: StoreEvent) {
+    let res = store.send(
+        TAMAGOTCHI,
+        StoreAction::ReturnAttribute {
+            attribute_id: SWORD,
+        },
+    );
+    assert!(res.contains(&Log::builder().dest(TAMAGOTCHI).payload(event)));
+}
+
+fn assert_refunded(ft: &Program<'_>, store: &Program<'_>) {
+    assert_balance(ft, TAMAGOTCHI, PRICE);
+
+    let state = read_state(store);
+    assert!(!state.owners[&TAMAGOTCHI.into()].contains(&SWORD));
+    assert!(state.receipts.is_empty());
+    assert!(state.refund_transactions.is_empty());
+    assert_eq!(state.refunded, PRICE);
+}
+
+#[test]
+fn admin_refunds_purchase() {
+    let sys = System::new();
+    sys.init_logger();
+    let (ft, store) = init(&sys);
+
+    let refund = StoreAction::Refund {
+        tamagotchi_id: TAMAGOTCHI.into(),
+        attribute_id: SWORD,
+    };
+    assert!(store.send(TAMAGOTCHI, refund).main_failed());
+
+    let res = store.send(
+        ADMIN,
+        StoreAction::Refund {
+            tamagotchi_id: TAMAGOTCHI.into(),
+            attribute_id: SWORD,
+        },
+    );
+    assert!(
+        res.contains(&Log::builder().dest(ADMIN).payload(StoreEvent::Refunded {
+            tamagotchi_id: TAMAGOTCHI.into(),
+            attribute_id: SWORD,
+            success: true,
+        }))
+    );
+    assert_refunded(&ft, &store);
+}
+
+#[test]
+fn return_within_window() {
+    let sys = System::new();
+    sys.init_logger();
+    let (ft, store) = init(&sys);
+
+    // Attributes without a window can't be returned.
+    return_attribute(
+        &store,
+        StoreEvent::ReturnWindowClosed {
+            attribute_id: SWORD,
+        },
+    );
+
+    set_return_window(&store, Some(RETURN_WINDOW));
+    return_attribute(
+        &store,
+        StoreEvent::Refunded {
+            tamagotchi_id: TAMAGOTCHI.into(),
+            attribute_id: SWORD,
+            success: true,
+        },
+    );
+    assert_refunded(&ft, &store);
+}
+
+#[test]
+fn return_after_window_is_rejected() {
+    let sys = System::new();
+    sys.init_logger();
+    let (_ft, store) = init(&sys);
+
+    set_return_window(&store, Some(RETURN_WINDOW));
+    sys.spend_blocks(RETURN_WINDOW + 1);
+    return_attribute(
+        &store,
+        StoreEvent::ReturnWindowClosed {
+            attribute_id: SWORD,
+        },
+    );
+
+    let state = read_state(&store);
+    assert!(state.owners[&TAMAGOTCHI.into()].contains(&SWORD));
+    assert_eq!(state.refunded, 0);
+}
@@ -0,0 +1,113 @@
+mod common;
+
+use common::{
+    assert_balance, buy, init_ft, init_store, read_state, ADMIN, FT_ID, PRICE, STORE_ID, SWORD,
+};
+use gtest::{Log, Program, System};
+use store_io::{StoreAction, StoreEvent};
+
+const TREASURY: u64 = 4;
+const STRANGER: u64 = 5;
+
+/// The tamagotchi has bought the sword with all its tokens.
+fn init(sys: &System) -> (Program<'_>, Program<'_>) {
+    let ft = init_ft(sys, PRICE);
+    let store = init_store(sys, FT_ID);
+    buy(
+        &store,
+        SWORD,
+        None,
+        StoreEvent::AttributeSold { success: true },
+    );
+    (ft, store)
+}
+
+fn withdraw(store: &Program<'_>, to: u64, amount: u128) {
+    let res = store.send(
+        ADMIN,
+        StoreAction::Withdraw {
+            to: to.into(),
+            amount,
+        },
+    );
+    assert!(
+        res.contains(&Log::builder().dest(ADMIN).payload(StoreEvent::Withdrawal {
+            to: to.into(),
+            amount,
+            success: true,
+        }))
+    );
+}
+
+#[test]
+fn withdraw_revenue() {
+    let sys = System::new();
+    sys.init_logger();
+    let (ft, store) = init(&sys);
+
+    let state = read_state(&store);
+    assert_eq!(state.revenue, PRICE);
+    assert_eq!(state.withdrawn, 0);
+
+    withdraw(&store, ADMIN, 60);
+    assert_balance(&ft, ADMIN, 60);
+
+    let res = store.send(
+        ADMIN,
+        StoreAction::SetTreasury {
+            treasury: TREASURY.into(),
+        },
+    );
+    assert!(res.contains(
+        &Log::builder().dest(ADMIN).payload(StoreEvent::TreasurySet {
+            treasury: TREASURY.into(),
+        })
+    ));
+    withdraw(&store, TREASURY, 40);
+    assert_balance(&ft, TREASURY, 40);
+    assert_balance(&ft, STORE_ID, 0);
+
+    // The whole revenue is withdrawn.
+    let transaction_id = read_state(&store).transaction_id;
+    let res = store.send(
+        ADMIN,
+        StoreAction::Withdraw {
+            to: TREASURY.into(),
+            amount: 1,
+        },
+    );
+    assert!(res.main_failed());
+
+    let state = read_state(&store);
+    assert_eq!(state.revenue, PRICE);
+    assert_eq!(state.withdrawn, PRICE);
+    assert_eq!(state.transaction_id, transaction_id);
+}
+
+#[test]
+fn withdrawal_destination_is_restricted() {
+    let sys = System::new();
+    sys.init_logger();
+    let (_ft, store) = init(&sys);
+
+    let res = store.send(
+        ADMIN,
+        StoreAction::Withdraw {
+            to: STRANGER.into(),
+            amount: PRICE,
+        },
+    );
+    assert!(res.main_failed());
+
+    let res = store.send(
+        STRANGER,
+        StoreAction::Withdraw {
+            to: ADMIN.into(),
+            amount: PRICE,
+        },
+    );
+    assert!(res.main_failed());
+
+    let state = read_state(&store);
+    assert_eq!(state.withdrawn, 0);
+}
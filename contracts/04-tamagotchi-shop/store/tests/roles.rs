@@ -0,0 +1,143 @@
+use gstd::ActorId;
+use gtest::{Log, Program, System};
+use store_io::{AttrMetadata, AttributeStore, StoreAction, StoreEvent};
+
+const ADMIN: u64 = 2;
+const NEW_ADMIN: u64 = 3;
+const OPERATOR: u64 = 4;
+const FT_CONTRACT: u64 = 5;
+
+fn init_store(sys: &System) -> Program<'_> {
+    sys.init_logger();
+    let store = Program::current(sys);
+    let res = store.send(ADMIN, ActorId::from(FT_CONTRACT));
+    assert!(!res.main_failed());
+    store
+}
+
+fn create_attribute(attribute_id: u32) -> StoreAction {
+    StoreAction::CreateAttribute {
+        attribute_id,
+        attr_metadata: AttrMetadata {
+            title: String::from("Sword"),
+            description: String::new(),
+            media: String::new(),
+            slot: None,
+            effects: None,
+        },
+        price: 100,
+    }
+}
+
+fn read_state(store: &Program<'_>) -> AttributeStore {
+    store.read_state(0).expect("Unable to read the store state")
+}
+
+#[test]
+fn two_step_admin_transfer() {
+    let sys = System::new();
+    let store = init_store(&sys);
+
+    assert!(store
+        .send(NEW_ADMIN, StoreAction::AcceptAdmin)
+        .main_failed());
+
+    let res = store.send(
+        ADMIN,
+        StoreAction::ProposeAdmin {
+            new_admin: NEW_ADMIN.into(),
+        },
+    );
+    assert!(res.contains(
+        &Log::builder()
+            .dest(ADMIN)
+            .payload(StoreEvent::AdminProposed {
+                new_admin: NEW_ADMIN.into(),
+            })
+    ));
+    let state = read_state(&store);
+    assert_eq!(state.admin, ADMIN.into());
+    assert_eq!(state.pending_admin, Some(NEW_ADMIN.into()));
+
+    let res = store.send(NEW_ADMIN, StoreAction::AcceptAdmin);
+    assert!(res.contains(
+        &Log::builder()
+            .dest(NEW_ADMIN)
+            .payload(StoreEvent::AdminChanged {
+                admin: NEW_ADMIN.into(),
+            })
+    ));
+    let state = read_state(&store);
+    assert_eq!(state.admin, NEW_ADMIN.into());
+    assert_eq!(state.pending_admin, None);
+
+    assert!(store.send(ADMIN, create_attribute(1)).main_failed());
+    assert!(!store.send(NEW_ADMIN, create_attribute(1)).main_failed());
+}
+
+#[test]
+fn operators_manage_attributes_only() {
+    let sys = System::new();
+    let store = init_store(&sys);
+
+    assert!(store.send(OPERATOR, create_attribute(1)).main_failed());
+
+    let res = store.send(
+        ADMIN,
+        StoreAction::AddOperator {
+            operator: OPERATOR.into(),
+        },
+    );
+    assert!(res.contains(
+        &Log::builder()
+            .dest(ADMIN)
+            .payload(StoreEvent::OperatorAdded {
+                operator: OPERATOR.into(),
+            })
+    ));
+
+    assert!(!store.send(OPERATOR, create_attribute(1)).main_failed());
+    let res = store.send(
+        OPERATOR,
+        StoreAction::UpdatePrice {
+            attribute_id: 1,
+            price: 50,
+        },
+    );
+    assert!(!res.main_failed());
+
+    let res = store.send(
+        OPERATOR,
+        StoreAction::SetFtContractId {
+            ft_contract_id: OPERATOR.into(),
+        },
+    );
+    assert!(res.main_failed());
+    let res = store.send(
+        OPERATOR,
+        StoreAction::AddOperator {
+            operator: NEW_ADMIN.into(),
+        },
+    );
+    assert!(res.main_failed());
+
+    let res = store.send(
+        ADMIN,
+        StoreAction::RemoveOperator {
+            operator: OPERATOR.into(),
+        },
+    );
+    assert!(res.contains(
+        &Log::builder()
+            .dest(ADMIN)
+            .payload(StoreEvent::OperatorRemoved {
+                operator: OPERATOR.into(),
+            })
+    ));
+    assert!(store.send(OPERATOR, create_attribute(2)).main_failed());
+
+    let state = read_state(&store);
+    assert_eq!(state.ft_contract_id, FT_CONTRACT.into());
+    assert!(state.operators.is_empty());
+    assert_eq!(state.attributes[&1].1, 50);
+}
@@ -0,0 +1,222 @@
+use gstd::ActorId;
+use gtest::{Log, Program, System};
+use store_io::{
+    coupon_hash, AttrMetadata, AttributeStore, Discount, SaleLimits, StoreAction, StoreEvent,
+};
+
+const ADMIN: u64 = 2;
+const TAMAGOTCHI: u64 = 3;
+const OTHER_TAMAGOTCHI: u64 = 4;
+// A plain account never replies, so purchases stay in progress.
+const FT_CONTRACT: u64 = 5;
+
+const ATTRIBUTE: u32 = 1;
+
+fn init_store(sys: &System) -> Program<'_> {
+    sys.init_logger();
+    let store = Program::current(sys);
+    let res = store.send(ADMIN, ActorId::from(FT_CONTRACT));
+    assert!(!res.main_failed());
+
+    let res = store.send(
+        ADMIN,
+        StoreAction::CreateAttribute {
+            attribute_id: ATTRIBUTE,
+            attr_metadata: AttrMetadata {
+                title: String::from("Limited hat"),
+                description: String::new(),
+                media: String::new(),
+                slot: None,
+                effects: None,
+            },
+            price: 100,
+        },
+    );
+    assert!(!res.main_failed());
+    store
+}
+
+fn set_sale_limits(
+    store: &Program<'_>,
+    max_supply: Option<u32>,
+    start_block: Option<u32>,
+    end_block: Option<u32>,
+) {
+    let res = store.send(
+        ADMIN,
+        StoreAction::SetSaleLimits {
+            attribute_id: ATTRIBUTE,
+            max_supply,
+            start_block,
+            end_block,
+        },
+    );
+    assert!(res.contains(
+        &Log::builder()
+            .dest(ADMIN)
+            .payload(StoreEvent::SaleLimitsSet {
+                attribute_id: ATTRIBUTE,
+                limits: SaleLimits {
+                    max_supply,
+                    sold: 0,
+                    start_block,
+                    end_block,
+                },
+            })
+    ));
+}
+
+fn assert_buy_reply(store: &Program<'_>, tamagotchi: u64, event: StoreEvent) {
+    let res = store.send(
+        tamagotchi,
+        StoreAction::BuyAttribute {
+            attribute_id: ATTRIBUTE,
+            coupon: None,
+        },
+    );
+    assert!(res.contains(&Log::builder().dest(tamagotchi).payload(event)));
+}
+
+fn read_state(store: &Program<'_>) -> AttributeStore {
+    store.read_state(0).expect("Unable to read the store state")
+}
+
+#[test]
+fn sale_window_is_enforced() {
+    let sys = System::new();
+    let store = init_store(&sys);
+
+    set_sale_limits(&store, None, Some(u32::MAX), None);
+    assert_buy_reply(
+        &store,
+        TAMAGOTCHI,
+        StoreEvent::SaleClosed {
+            attribute_id: ATTRIBUTE,
+        },
+    );
+
+    sys.spend_blocks(10);
+    set_sale_limits(&store, None, None, Some(5));
+    assert_buy_reply(
+        &store,
+        TAMAGOTCHI,
+        StoreEvent::SaleClosed {
+            attribute_id: ATTRIBUTE,
+        },
+    );
+
+    let state = read_state(&store);
+    assert!(state.transactions.is_empty());
+    assert_eq!(state.sales[&ATTRIBUTE].sold, 0);
+}
+
+#[test]
+fn supply_counts_purchases_in_progress() {
+    let sys = System::new();
+    let store = init_store(&sys);
+
+    set_sale_limits(&store, Some(1), None, None);
+
+    let res = store.send(
+        TAMAGOTCHI,
+        StoreAction::BuyAttribute {
+            attribute_id: ATTRIBUTE,
+            coupon: None,
+        },
+    );
+    assert!(!res.main_failed());
+    let state = read_state(&store);
+    assert_eq!(state.sales[&ATTRIBUTE].sold, 1);
+    assert!(state.transactions.contains_key(&TAMAGOTCHI.into()));
+
+    assert_buy_reply(
+        &store,
+        OTHER_TAMAGOTCHI,
+        StoreEvent::SoldOut {
+            attribute_id: ATTRIBUTE,
+        },
+    );
+    assert!(!read_state(&store)
+        .transactions
+        .contains_key(&OTHER_TAMAGOTCHI.into()));
+}
+
+#[test]
+fn removed_purchase_gives_unit_back() {
+    let sys = System::new();
+    let store = init_store(&sys);
+
+    set_sale_limits(&store, Some(1), None, None);
+    let res = store.send(
+        TAMAGOTCHI,
+        StoreAction::BuyAttribute {
+            attribute_id: ATTRIBUTE,
+            coupon: None,
+        },
+    );
+    assert!(!res.main_failed());
+
+    let res = store.send(
+        ADMIN,
+        StoreAction::RemoveTx {
+            tamagotchi_id: TAMAGOTCHI.into(),
+        },
+    );
+    assert!(
+        res.contains(&Log::builder().dest(ADMIN).payload(StoreEvent::TxRemoved {
+            tamagotchi_id: TAMAGOTCHI.into(),
+        }))
+    );
+    assert_eq!(read_state(&store).sales[&ATTRIBUTE].sold, 0);
+
+    let res = store.send(
+        OTHER_TAMAGOTCHI,
+        StoreAction::BuyAttribute {
+            attribute_id: ATTRIBUTE,
+            coupon: None,
+        },
+    );
+    assert!(!res.main_failed());
+    assert!(read_state(&store)
+        .transactions
+        .contains_key(&OTHER_TAMAGOTCHI.into()));
+}
+
+#[test]
+fn removed_purchase_gives_coupon_back() {
+    let sys = System::new();
+    let store = init_store(&sys);
+
+    let res = store.send(
+        ADMIN,
+        StoreAction::AddCoupon {
+            coupon_hash: coupon_hash("WELCOME"),
+            discount: Discount::Percent(10),
+        },
+    );
+    assert!(!res.main_failed());
+    let res = store.send(
+        TAMAGOTCHI,
+        StoreAction::BuyAttribute {
+            attribute_id: ATTRIBUTE,
+            coupon: Some(String::from("WELCOME")),
+        },
+    );
+    assert!(!res.main_failed());
+    assert!(read_state(&store).coupons.is_empty());
+
+    let res = store.send(
+        ADMIN,
+        StoreAction::RemoveTx {
+            tamagotchi_id: TAMAGOTCHI.into(),
+        },
+    );
+    assert!(!res.main_failed());
+
+    let state = read_state(&store);
+    assert!(state.transactions.is_empty());
+    assert_eq!(
+        state.coupons[&coupon_hash("WELCOME")],
+        Discount::Percent(10)
+    );
+}
@@ -0,0 +1,145 @@
+mod common;
+
+use common::{
+    assert_balance, init_ft, init_store, read_state, ADMIN, BALANCE, FT_ID, PRICE, SWORD,
+    TAMAGOTCHI,
+};
+use gtest::{Log, Program, System};
+use store_io::{StoreAction, StoreEvent};
+
+const FRIEND: u64 = 4;
+const MARKETPLACE: u64 = 5;
+
+fn init(sys: &System) -> (Program<'_>, Program<'_>) {
+    let ft = init_ft(sys, BALANCE);
+    let store = init_store(sys, FT_ID);
+    (ft, store)
+}
+
+#[test]
+fn gift_is_paid_by_the_buyer_and_owned_by_the_recipient() {
+    let sys = System::new();
+    sys.init_logger();
+    let (ft, store) = init(&sys);
+
+    let res = store.send(
+        TAMAGOTCHI,
+        StoreAction::BuyAttributeFor {
+            attribute_id: SWORD,
+            recipient: FRIEND.into(),
+            coupon: None,
+        },
+    );
+    assert!(res.contains(
+        &Log::builder()
+            .dest(TAMAGOTCHI)
+            .payload(StoreEvent::AttributeSold { success: true })
+    ));
+    assert_balance(&ft, TAMAGOTCHI, BALANCE - PRICE);
+
+    let state = read_state(&store);
+    assert!(!state.owners.contains_key(&ActorId::from(TAMAGOTCHI)));
+    assert_eq!(state.owners[&ActorId::from(FRIEND)], [SWORD].into());
+    assert!(state.receipts.contains_key(&(ActorId::from(FRIEND), SWORD)));
+    assert!(state.transactions.is_empty());
+}
+
+#[test]
+fn owner_transfers_attribute() {
+    let sys = System::new();
+    sys.init_logger();
+    let (_ft, store) = init(&sys);
+
+    let res = store.send(
+        TAMAGOTCHI,
+        StoreAction::BuyAttribute {
+            attribute_id: SWORD,
+            coupon: None,
+        },
+    );
+    assert!(!res.main_failed());
+
+    let res = store.send(
+        FRIEND,
+        StoreAction::TransferAttribute {
+            to: TAMAGOTCHI.into(),
+            attribute_id: SWORD,
+        },
+    );
+    assert!(res.main_failed());
+
+    let res = store.send(
+        TAMAGOTCHI,
+        StoreAction::TransferAttribute {
+            to: FRIEND.into(),
+            attribute_id: SWORD,
+        },
+    );
+    assert!(res.contains(&Log::builder().dest(TAMAGOTCHI).payload(
+        StoreEvent::AttributeTransferred {
+            from: TAMAGOTCHI.into(),
+            to: FRIEND.into(),
+            attribute_id: SWORD,
+        }
+    )));
+
+    let state = read_state(&store);
+    assert!(!state.owners.contains_key(&ActorId::from(TAMAGOTCHI)));
+    assert_eq!(state.owners[&ActorId::from(FRIEND)], [SWORD].into());
+    assert!(state.receipts.is_empty());
+}
+
+#[test]
+fn royalty_is_booked_once_paid() {
+    let sys = System::new();
+    sys.init_logger();
+    let (_ft, store) = init(&sys);
+
+    let res = store.send(
+        TAMAGOTCHI,
+        StoreAction::BuyAttribute {
+            attribute_id: SWORD,
+            coupon: None,
+        },
+    );
+    assert!(!res.main_failed());
+    let res = store.send(
+        ADMIN,
+        StoreAction::AddMarketplace {
+            marketplace: MARKETPLACE.into(),
+        },
+    );
+    assert!(!res.main_failed());
+
+    let res = store.send(
+        MARKETPLACE,
+        StoreAction::MarketplaceTransfer {
+            from: TAMAGOTCHI.into(),
+            to: FRIEND.into(),
+            attribute_id: SWORD,
+            royalty: 10,
+        },
+    );
+    assert!(!res.main_failed());
+    let state = read_state(&store);
+    assert_eq!(state.revenue, PRICE);
+    assert_eq!(state.royalties_due[&ActorId::from(MARKETPLACE)], 10);
+
+    for (from, royalty) in [(FRIEND, 10), (MARKETPLACE, 11)] {
+        let res = store.send(from, StoreAction::ConfirmRoyalty { royalty });
+        assert!(res.main_failed());
+    }
+    let res = store.send(MARKETPLACE, StoreAction::ConfirmRoyalty { royalty: 10 });
+    assert!(res.contains(
+        &Log::builder()
+            .dest(MARKETPLACE)
+            .payload(StoreEvent::RoyaltyReceived {
+                marketplace: MARKETPLACE.into(),
+                royalty: 10,
+            })
+    ));
+
+    let state = read_state(&store);
+    assert_eq!(state.revenue, PRICE + 10);
+    assert!(state.royalties_due.is_empty());
+}
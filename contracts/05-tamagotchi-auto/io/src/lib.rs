@@ -3,6 +3,8 @@ use gmeta::{In, InOut, Metadata, Out};
 use gstd::{prelude::*, ActorId};
 use store_io::{AttributeId, TransactionId};
 
+pub use tamagotchi_core::TmgError;
+
 #[derive(Default, Encode, Decode, TypeInfo)]
 #[codec(crate = gstd::codec)]
 #[scale_info(crate = gstd::scale_info)]
@@ -68,6 +70,7 @@ pub enum TmgEvent {
     AttributeBought(AttributeId),
     CompletePrevPurchase(AttributeId),
     ErrorDuringPurchase,
+    Error(TmgError),
     // TODO: 3️⃣ Add new events
 }
 
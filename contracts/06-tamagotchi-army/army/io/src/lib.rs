@@ -0,0 +1,82 @@
+#![no_std]
+
+use gmeta::{In, InOut, Metadata as GMetadata, Out};
+use gstd::{
+    collections::{BTreeMap, BTreeSet},
+    prelude::*,
+    ActorId, CodeId,
+};
+
+pub type TamagotchiId = ActorId;
+
+/// Most tamagotchis a paginated query returns.
+pub const MAX_PAGE_SIZE: u32 = 50;
+
+pub struct ProgramMetadata;
+
+impl GMetadata for ProgramMetadata {
+    type Init = In<CodeId>;
+    type Handle = InOut<ArmyAction, ArmyEvent>;
+    type Reply = ();
+    type Others = ();
+    type Signal = ();
+    type State = Out<Army>;
+}
+
+#[derive(Default, Encode, Decode, TypeInfo)]
+#[codec(crate = gstd::codec)]
+#[scale_info(crate = gstd::scale_info)]
+pub struct Army {
+    pub tamagotchi_code_id: CodeId,
+    pub tamagotchis: BTreeMap<TamagotchiId, TamagotchiInfo>,
+    /// Tamagotchis by the account that created them.
+    pub owners: BTreeMap<ActorId, BTreeSet<TamagotchiId>>,
+}
+
+#[derive(Encode, Decode, Clone, TypeInfo, Debug, PartialEq, Eq)]
+#[codec(crate = gstd::codec)]
+#[scale_info(crate = gstd::scale_info)]
+pub struct TamagotchiInfo {
+    /// Account that created the tamagotchi through the army.
+    ///
+    /// The army isn't told about `TmgAction::Transfer`, so this stays the creator after the
+    /// tamagotchi changes hands. The current owner is in the tamagotchi's own state.
+    pub owner: ActorId,
+    pub name: String,
+}
+
+#[derive(Encode, Decode, TypeInfo, Debug)]
+#[codec(crate = gstd::codec)]
+#[scale_info(crate = gstd::scale_info)]
+pub enum ArmyAction {
+    CreateTamagotchi {
+        name: String,
+    },
+    GetTamagotchis {
+        offset: u32,
+        limit: u32,
+    },
+    /// Tamagotchis created by `owner`, whoever owns them now.
+    GetTamagotchisByOwner {
+        owner: ActorId,
+    },
+}
+
+#[derive(Encode, Decode, TypeInfo, Debug, PartialEq, Eq)]
+#[codec(crate = gstd::codec)]
+#[scale_info(crate = gstd::scale_info)]
+pub enum ArmyEvent {
+    TamagotchiCreated {
+        tamagotchi_id: TamagotchiId,
+        owner: ActorId,
+        name: String,
+    },
+    Tamagotchis {
+        tamagotchis: Vec<(TamagotchiId, TamagotchiInfo)>,
+        total: u32,
+    },
+    OwnerTamagotchis {
+        owner: ActorId,
+        tamagotchis: BTreeSet<TamagotchiId>,
+    },
+}
@@ -0,0 +1,132 @@
+#![no_std]
+
+use army_io::{ArmyAction, ArmyEvent, TamagotchiId, TamagotchiInfo, MAX_PAGE_SIZE};
+use gstd::{
+    collections::{BTreeMap, BTreeSet},
+    msg,
+    prelude::*,
+    prog::ProgramGenerator,
+    ActorId, CodeId,
+};
+use tamagotchi_utils_io::TmgInit;
+
+const GAS_FOR_CREATION: u64 = 5_000_000_000;
+
+static mut ARMY: Option<Army> = None;
+
+#[derive(Default)]
+struct Army {
+    tamagotchi_code_id: CodeId,
+    tamagotchis: BTreeMap<TamagotchiId, TamagotchiInfo>,
+    owners: BTreeMap<ActorId, BTreeSet<TamagotchiId>>,
+}
+
+impl Army {
+    async fn create_tamagotchi(&mut self, name: String) {
+        let owner = msg::source();
+        let (tamagotchi_id, _) = ProgramGenerator::create_program_with_gas_for_reply(
+            self.tamagotchi_code_id,
+            TmgInit {
+                owner,
+                name: name.clone(),
+                config: None,
+            },
+            GAS_FOR_CREATION,
+            0,
+            0,
+        )
+        .expect("Error in creating a tamagotchi program")
+        .await
+        .expect("Tamagotchi program was not initialized");
+
+        self.tamagotchis.insert(
+            tamagotchi_id,
+            TamagotchiInfo {
+                owner,
+                name: name.clone(),
+            },
+        );
+        self.owners
+            .entry(owner)
+            .and_modify(|tamagotchis| {
+                tamagotchis.insert(tamagotchi_id);
+            })
+            .or_insert_with(|| [tamagotchi_id].into());
+
+        msg::reply(
+            ArmyEvent::TamagotchiCreated {
+                tamagotchi_id,
+                owner,
+                name,
+            },
+            0,
+        )
+        .expect("Error in sending a reply `ArmyEvent::TamagotchiCreated`");
+    }
+
+    fn get_tamagotchis(&self, offset: u32, limit: u32) {
+        let tamagotchis = self
+            .tamagotchis
+            .iter()
+            .skip(offset as usize)
+            .take(limit.min(MAX_PAGE_SIZE) as usize)
+            .map(|(id, info)| (*id, info.clone()))
+            .collect();
+        msg::reply(
+            ArmyEvent::Tamagotchis {
+                tamagotchis,
+                total: self.tamagotchis.len() as u32,
+            },
+            0,
+        )
+        .expect("Error in sending a reply `ArmyEvent::Tamagotchis`");
+    }
+
+    fn get_tamagotchis_by_owner(&self, owner: &ActorId) {
+        let tamagotchis = self.owners.get(owner).cloned().unwrap_or_default();
+        msg::reply(
+            ArmyEvent::OwnerTamagotchis {
+                owner: *owner,
+                tamagotchis,
+            },
+            0,
+        )
+        .expect("Error in sending a reply `ArmyEvent::OwnerTamagotchis`");
+    }
+}
+
+#[gstd::async_main]
+async fn main() {
+    let action: ArmyAction = msg::load().expect("Unable to decode `ArmyAction`");
+    let army: &mut Army = unsafe { ARMY.as_mut().expect("The contract is not initialized") };
+    match action {
+        ArmyAction::CreateTamagotchi { name } => army.create_tamagotchi(name).await,
+        ArmyAction::GetTamagotchis { offset, limit } => army.get_tamagotchis(offset, limit),
+        ArmyAction::GetTamagotchisByOwner { owner } => army.get_tamagotchis_by_owner(&owner),
+    }
+}
+
+#[no_mangle]
+extern fn init() {
+    let tamagotchi_code_id: CodeId = msg::load().expect("Unable to decode `CodeId`");
+    let army = Army {
+        tamagotchi_code_id,
+        ..Default::default()
+    };
+    unsafe { ARMY = Some(army) };
+}
+
+#[no_mangle]
+extern fn state() {
+    let army = unsafe { ARMY.as_ref().expect("The contract is not initialized") };
+
+    msg::reply(
+        army_io::Army {
+            tamagotchi_code_id: army.tamagotchi_code_id,
+            tamagotchis: army.tamagotchis.clone(),
+            owners: army.owners.clone(),
+        },
+        0,
+    )
+    .expect("Failed to share state");
+}
@@ -0,0 +1,153 @@
+use army_io::{Army, ArmyAction, ArmyEvent, TamagotchiInfo};
+use gstd::{ActorId, CodeId};
+use gtest::{Log, Program, System};
+use tamagotchi_utils_io::{Tamagotchi, TmgAction, TmgEvent};
+
+const TAMAGOTCHI_WASM: &str = "../../target/wasm32-unknown-unknown/debug/tamagotchi_utils.opt.wasm";
+const ADMIN: u64 = 2;
+const ALICE: u64 = 3;
+const BOB: u64 = 4;
+
+fn init_army(sys: &System) -> Program<'_> {
+    sys.init_logger();
+    let code_id = sys.submit_code(TAMAGOTCHI_WASM);
+    let army = Program::current(sys);
+    let res = army.send(ADMIN, CodeId::from(code_id.into_bytes()));
+    assert!(!res.main_failed());
+    army
+}
+
+fn create_tamagotchi(army: &Program<'_>, user: u64, name: &str) {
+    let res = army.send(
+        user,
+        ArmyAction::CreateTamagotchi {
+            name: name.to_string(),
+        },
+    );
+    assert!(!res.main_failed());
+}
+
+#[test]
+fn create_by_multiple_users() {
+    let sys = System::new();
+    let army = init_army(&sys);
+
+    create_tamagotchi(&army, ALICE, "Alpha");
+    create_tamagotchi(&army, BOB, "Beta");
+    create_tamagotchi(&army, ALICE, "Gamma");
+
+    let state: Army = army.read_state(0).expect("Unable to read the army state");
+    assert_eq!(state.tamagotchis.len(), 3);
+    assert_eq!(state.owners[&ActorId::from(ALICE)].len(), 2);
+    assert_eq!(state.owners[&ActorId::from(BOB)].len(), 1);
+
+    for (tamagotchi_id, TamagotchiInfo { owner, name }) in &state.tamagotchis {
+        let tamagotchi = sys.get_program(tamagotchi_id.as_ref());
+        let user = if *owner == ActorId::from(ALICE) {
+            ALICE
+        } else {
+            BOB
+        };
+        assert!(state.owners[owner].contains(tamagotchi_id));
+
+        let res = tamagotchi.send(user, TmgAction::Name);
+        let expected_log = Log::builder()
+            .dest(user)
+            .payload(TmgEvent::Name(name.clone()));
+        assert!(res.contains(&expected_log));
+    }
+}
+
+#[test]
+fn lookup_and_pagination() {
+    let sys = System::new();
+    let army = init_army(&sys);
+
+    create_tamagotchi(&army, ALICE, "Alpha");
+    create_tamagotchi(&army, BOB, "Beta");
+    create_tamagotchi(&army, BOB, "Gamma");
+
+    let state: Army = army.read_state(0).expect("Unable to read the army state");
+
+    let res = army.send(
+        ALICE,
+        ArmyAction::GetTamagotchisByOwner { owner: BOB.into() },
+    );
+    let expected_log = Log::builder()
+        .dest(ALICE)
+        .payload(ArmyEvent::OwnerTamagotchis {
+            owner: BOB.into(),
+            tamagotchis: state.owners[&ActorId::from(BOB)].clone(),
+        });
+    assert!(res.contains(&expected_log));
+
+    let res = army.send(
+        ALICE,
+        ArmyAction::GetTamagotchisByOwner {
+            owner: ADMIN.into(),
+        },
+    );
+    let expected_log = Log::builder()
+        .dest(ALICE)
+        .payload(ArmyEvent::OwnerTamagotchis {
+            owner: ADMIN.into(),
+            tamagotchis: Default::default(),
+        });
+    assert!(res.contains(&expected_log));
+
+    let res = army.send(
+        ALICE,
+        ArmyAction::GetTamagotchis {
+            offset: 1,
+            limit: 5,
+        },
+    );
+    let expected_log = Log::builder().dest(ALICE).payload(ArmyEvent::Tamagotchis {
+        tamagotchis: state
+            .tamagotchis
+            .iter()
+            .skip(1)
+            .map(|(id, info)| (*id, info.clone()))
+            .collect(),
+        total: 3,
+    });
+    assert!(res.contains(&expected_log));
+}
+
+#[test]
+fn registry_keeps_creator_after_transfer() {
+    let sys = System::new();
+    let army = init_army(&sys);
+    create_tamagotchi(&army, ALICE, "Alpha");
+
+    let state: Army = army.read_state(0).expect("Unable to read the army state");
+    let tamagotchi_id = *state.owners[&ActorId::from(ALICE)]
+        .first()
+        .expect("Alice has no tamagotchi");
+    let tamagotchi = sys.get_program(tamagotchi_id.as_ref());
+    let res = tamagotchi.send(ALICE, TmgAction::Transfer(BOB.into()));
+    assert!(res.contains(
+        &Log::builder()
+            .dest(ALICE)
+            .payload(TmgEvent::Transferred(BOB.into()))
+    ));
+    let state: Tamagotchi = tamagotchi
+        .read_state(0)
+        .expect("Unable to read the tamagotchi state");
+    assert_eq!(state.owner, BOB.into());
+
+    let res = army.send(
+        BOB,
+        ArmyAction::GetTamagotchisByOwner {
+            owner: ALICE.into(),
+        },
+    );
+    assert!(res.contains(
+        &Log::builder()
+            .dest(BOB)
+            .payload(ArmyEvent::OwnerTamagotchis {
+                owner: ALICE.into(),
+                tamagotchis: [tamagotchi_id].into(),
+            })
+    ));
+}
@@ -0,0 +1,28 @@
+#![allow(dead_code)]
+
+use gtest::{Log, Program, System};
+use tamagotchi_army_io::{Tamagotchi, TmgAction, TmgEvent};
+
+pub const OWNER: u64 = 2;
+pub const STRANGER: u64 = 3;
+
+pub const RESERVATION_AMOUNT: u64 = 10_000_000_000;
+
+pub fn init_tamagotchi(sys: &System) -> Program<'_> {
+    sys.init_logger();
+    let tamagotchi = Program::current(sys);
+    let res = tamagotchi.send(OWNER, String::from("Luchex"));
+    assert!(!res.main_failed());
+    tamagotchi
+}
+
+pub fn read_state(tamagotchi: &Program<'_>) -> Tamagotchi {
+    tamagotchi
+        .read_state(0)
+        .expect("Unable to read the tamagotchi state")
+}
+
+pub fn assert_reply(tamagotchi: &Program<'_>, from: u64, action: TmgAction, event: TmgEvent) {
+    let res = tamagotchi.send(from, action);
+    assert!(res.contains(&Log::builder().dest(from).payload(event)));
+}
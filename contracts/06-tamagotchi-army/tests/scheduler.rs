@@ -0,0 +1,211 @@
+mod common;
+
+use common::{assert_reply, init_tamagotchi, read_state, OWNER, RESERVATION_AMOUNT, STRANGER};
+use gtest::{Log, Program, System};
+use tamagotchi_army_io::{TmgAction, TmgError, TmgEvent};
+
+const CARETAKER: u64 = 4;
+/// Subscribers a tamagotchi keeps at most.
+const MAX_SUBSCRIBERS: u64 = 10;
+
+/// Blocks between two `CheckState` messages of the loop by default.
+const DELAY: u32 = 20;
+
+/// The first reservation pays for the `CheckState` message that starts the loop.
+fn reserve_gas(tamagotchi: &Program<'_>, durations: &[u32]) {
+    for &duration in durations {
+        let res = tamagotchi.send(
+            OWNER,
+            TmgAction::ReserveGas {
+                reservation_amount: RESERVATION_AMOUNT,
+                duration,
+            },
+        );
+        assert!(res.contains(&Log::builder().dest(OWNER).payload(TmgEvent::GasReserved)));
+    }
+}
+
+#[test]
+fn reservations_expiring_first_are_used_first() {
+    let sys = System::new();
+    let tamagotchi = init_tamagotchi(&sys);
+    reserve_gas(&tamagotchi, &[1000, 600, 300]);
+
+    let state = read_state(&tamagotchi);
+    assert_eq!(state.reservations.len(), 2);
+    assert!(state.reservations[0].expires_at < state.reservations[1].expires_at);
+    assert_eq!(state.runway, 2 * DELAY);
+}
+
+#[test]
+fn owner_is_warned_before_gas_runs_out() {
+    let sys = System::new();
+    let tamagotchi = init_tamagotchi(&sys);
+    reserve_gas(&tamagotchi, &[1000, 1000, 1000]);
+
+    sys.spend_blocks(DELAY);
+    let mailbox = sys.get_mailbox(OWNER);
+    assert!(mailbox.contains(
+        &Log::builder()
+            .dest(OWNER)
+            .payload(TmgEvent::LowGasRunway { blocks: DELAY })
+    ));
+    assert!(!mailbox.contains(
+        &Log::builder()
+            .dest(OWNER)
+            .payload(TmgEvent::MakeReservation)
+    ));
+
+    sys.spend_blocks(2 * DELAY);
+    let mailbox = sys.get_mailbox(OWNER);
+    assert!(mailbox.contains(
+        &Log::builder()
+            .dest(OWNER)
+            .payload(TmgEvent::MakeReservation)
+    ));
+
+    let state = read_state(&tamagotchi);
+    assert!(state.reservations.is_empty());
+    assert_eq!(state.runway, 0);
+}
+
+#[test]
+fn owner_unreserves_leftover_gas() {
+    let sys = System::new();
+    let tamagotchi = init_tamagotchi(&sys);
+    reserve_gas(&tamagotchi, &[1000, 1000]);
+
+    let res = tamagotchi.send(STRANGER, TmgAction::UnreserveGas);
+    assert!(res.contains(
+        &Log::builder()
+            .dest(STRANGER)
+            .payload(TmgEvent::Error(TmgError::NotOwner))
+    ));
+
+    let res = tamagotchi.send(OWNER, TmgAction::UnreserveGas);
+    assert!(res.contains(
+        &Log::builder()
+            .dest(OWNER)
+            .payload(TmgEvent::GasUnreserved(1))
+    ));
+    assert!(read_state(&tamagotchi).reservations.is_empty());
+
+    // the check already on its way doesn't ask for more gas
+    sys.spend_blocks(DELAY);
+    let mailbox = sys.get_mailbox(OWNER);
+    assert!(!mailbox.contains(
+        &Log::builder()
+            .dest(OWNER)
+            .payload(TmgEvent::MakeReservation)
+    ));
+}
+
+#[test]
+fn subscribers_follow_configured_interval() {
+    let sys = System::new();
+    let tamagotchi = init_tamagotchi(&sys);
+    let interval = DELAY / 2;
+
+    assert_reply(
+        &tamagotchi,
+        OWNER,
+        TmgAction::SetCheckInterval(0),
+        TmgEvent::Error(TmgError::InvalidCheckInterval),
+    );
+    assert_reply(
+        &tamagotchi,
+        STRANGER,
+        TmgAction::Subscribe(STRANGER.into()),
+        TmgEvent::Error(TmgError::NotOwner),
+    );
+    assert_reply(
+        &tamagotchi,
+        OWNER,
+        TmgAction::SetCheckInterval(interval),
+        TmgEvent::CheckIntervalSet(interval),
+    );
+    assert_reply(
+        &tamagotchi,
+        OWNER,
+        TmgAction::Subscribe(CARETAKER.into()),
+        TmgEvent::Subscribed(CARETAKER.into()),
+    );
+    let state = read_state(&tamagotchi);
+    assert_eq!(state.check_interval, interval);
+    assert!(state.subscribers.contains(&CARETAKER.into()));
+
+    // the only reservation pays for the first check, which runs out of gas
+    reserve_gas(&tamagotchi, &[1000]);
+    sys.spend_blocks(interval);
+    for account in [OWNER, CARETAKER] {
+        assert!(sys.get_mailbox(account).contains(
+            &Log::builder()
+                .dest(account)
+                .payload(TmgEvent::MakeReservation)
+        ));
+    }
+}
+
+#[test]
+fn subscriber_unsubscribes_itself() {
+    let sys = System::new();
+    let tamagotchi = init_tamagotchi(&sys);
+    assert_reply(
+        &tamagotchi,
+        OWNER,
+        TmgAction::Subscribe(CARETAKER.into()),
+        TmgEvent::Subscribed(CARETAKER.into()),
+    );
+
+    assert_reply(
+        &tamagotchi,
+        STRANGER,
+        TmgAction::Unsubscribe(CARETAKER.into()),
+        TmgEvent::Error(TmgError::NotOwner),
+    );
+    assert_reply(
+        &tamagotchi,
+        CARETAKER,
+        TmgAction::Unsubscribe(CARETAKER.into()),
+        TmgEvent::Unsubscribed(CARETAKER.into()),
+    );
+    assert_reply(
+        &tamagotchi,
+        CARETAKER,
+        TmgAction::Unsubscribe(CARETAKER.into()),
+        TmgEvent::Error(TmgError::NotSubscribed),
+    );
+    assert!(read_state(&tamagotchi).subscribers.is_empty());
+}
+
+#[test]
+fn subscribers_are_capped() {
+    let sys = System::new();
+    let tamagotchi = init_tamagotchi(&sys);
+    let first = 100;
+    for account in first..first + MAX_SUBSCRIBERS {
+        assert_reply(
+            &tamagotchi,
+            OWNER,
+            TmgAction::Subscribe(account.into()),
+            TmgEvent::Subscribed(account.into()),
+        );
+    }
+
+    assert_reply(
+        &tamagotchi,
+        OWNER,
+        TmgAction::Subscribe(CARETAKER.into()),
+        TmgEvent::Error(TmgError::TooManySubscribers),
+    );
+    // subscribing an account again doesn't take another place
+    assert_reply(
+        &tamagotchi,
+        OWNER,
+        TmgAction::Subscribe(first.into()),
+        TmgEvent::Subscribed(first.into()),
+    );
+    let state = read_state(&tamagotchi);
+    assert_eq!(state.subscribers.len() as u64, MAX_SUBSCRIBERS);
+    assert!(!state.subscribers.contains(&CARETAKER.into()));
+}
@@ -0,0 +1,176 @@
+mod common;
+
+use common::{assert_reply, init_tamagotchi, read_state, OWNER, RESERVATION_AMOUNT, STRANGER};
+use gtest::{Log, Program, System};
+use tamagotchi_army_io::{Sponsorship, TmgAction, TmgError, TmgEvent};
+
+const SPONSOR: u64 = 4;
+const FRIEND: u64 = 5;
+const VALUE: u128 = 10_000_000_000_000;
+/// Sponsored value paid back for a unit of gas reserved with `ReserveGas`.
+const VALUE_PER_GAS: u128 = 25;
+/// Blocks between two `CheckState` messages of the loop by default.
+const DELAY: u32 = 20;
+
+/// The sponsor reserves gas once and gives value twice.
+fn sponsor(sys: &System, tamagotchi: &Program<'_>) {
+    sys.mint_to(SPONSOR, 3 * VALUE);
+    for (reservation_amount, duration) in [(RESERVATION_AMOUNT, 1000), (0, 0)] {
+        let res = tamagotchi.send_with_value(
+            SPONSOR,
+            TmgAction::Sponsor {
+                reservation_amount,
+                duration,
+            },
+            VALUE,
+        );
+        assert!(
+            res.contains(&Log::builder().dest(SPONSOR).payload(TmgEvent::Sponsored {
+                sponsor: SPONSOR.into(),
+                gas: reservation_amount,
+                value: VALUE,
+            }))
+        );
+    }
+}
+
+#[test]
+fn sponsors_are_recorded() {
+    let sys = System::new();
+    let tamagotchi = init_tamagotchi(&sys);
+    sponsor(&sys, &tamagotchi);
+
+    assert_reply(
+        &tamagotchi,
+        STRANGER,
+        TmgAction::Sponsor {
+            reservation_amount: 0,
+            duration: 0,
+        },
+        TmgEvent::Error(TmgError::NothingSponsored),
+    );
+
+    let state = read_state(&tamagotchi);
+    assert_eq!(
+        state.sponsors[&SPONSOR.into()],
+        Sponsorship {
+            gas: RESERVATION_AMOUNT,
+            value: 2 * VALUE,
+        }
+    );
+    assert!(!state.sponsors.contains_key(&STRANGER.into()));
+    assert_eq!(state.sponsored_value, 2 * VALUE);
+}
+
+#[test]
+fn owner_withdraws_sponsored_value() {
+    let sys = System::new();
+    let tamagotchi = init_tamagotchi(&sys);
+    sponsor(&sys, &tamagotchi);
+
+    assert_reply(
+        &tamagotchi,
+        STRANGER,
+        TmgAction::WithdrawSponsorship(VALUE),
+        TmgEvent::Error(TmgError::NotOwner),
+    );
+    assert_reply(
+        &tamagotchi,
+        OWNER,
+        TmgAction::WithdrawSponsorship(2 * VALUE + 1),
+        TmgEvent::Error(TmgError::NotEnoughSponsoredValue),
+    );
+    assert_reply(
+        &tamagotchi,
+        OWNER,
+        TmgAction::WithdrawSponsorship(VALUE),
+        TmgEvent::SponsorshipWithdrawn(VALUE),
+    );
+
+    let state = read_state(&tamagotchi);
+    assert_eq!(state.sponsored_value, VALUE);
+    assert_eq!(
+        state.sponsors[&SPONSOR.into()],
+        Sponsorship {
+            gas: RESERVATION_AMOUNT,
+            value: VALUE,
+        }
+    );
+}
+
+#[test]
+fn value_of_other_actions_is_not_sponsored() {
+    let sys = System::new();
+    let tamagotchi = init_tamagotchi(&sys);
+    sys.mint_to(STRANGER, VALUE);
+
+    let res = tamagotchi.send_with_value(STRANGER, TmgAction::Feed, VALUE);
+    assert!(res.contains(&Log::builder().dest(STRANGER).payload(TmgEvent::Fed)));
+
+    let state = read_state(&tamagotchi);
+    assert!(state.sponsors.is_empty());
+    assert_eq!(state.sponsored_value, 0);
+}
+
+#[test]
+fn withdrawal_is_shared_by_sponsors() {
+    let sys = System::new();
+    let tamagotchi = init_tamagotchi(&sys);
+    sponsor(&sys, &tamagotchi);
+    sys.mint_to(FRIEND, VALUE);
+    let res = tamagotchi.send_with_value(
+        FRIEND,
+        TmgAction::Sponsor {
+            reservation_amount: 0,
+            duration: 0,
+        },
+        VALUE,
+    );
+    assert!(!res.main_failed());
+
+    // half of the sponsored value is taken from both sponsors
+    assert_reply(
+        &tamagotchi,
+        OWNER,
+        TmgAction::WithdrawSponsorship(3 * VALUE / 2),
+        TmgEvent::SponsorshipWithdrawn(3 * VALUE / 2),
+    );
+    let state = read_state(&tamagotchi);
+    assert_eq!(state.sponsored_value, 3 * VALUE / 2);
+    assert_eq!(state.sponsors[&SPONSOR.into()].value, VALUE);
+    assert_eq!(state.sponsors[&FRIEND.into()].value, VALUE / 2);
+}
+
+#[test]
+fn sponsored_value_pays_for_reservations() {
+    let sys = System::new();
+    let tamagotchi = init_tamagotchi(&sys);
+    sponsor(&sys, &tamagotchi);
+
+    // a reservation gone before the next check isn't paid for
+    assert_reply(
+        &tamagotchi,
+        OWNER,
+        TmgAction::ReserveGas {
+            reservation_amount: RESERVATION_AMOUNT,
+            duration: DELAY - 1,
+        },
+        TmgEvent::GasReserved,
+    );
+    assert_eq!(read_state(&tamagotchi).sponsored_value, 2 * VALUE);
+
+    assert_reply(
+        &tamagotchi,
+        OWNER,
+        TmgAction::ReserveGas {
+            reservation_amount: RESERVATION_AMOUNT,
+            duration: 1000,
+        },
+        TmgEvent::GasReserved,
+    );
+    let paid = u128::from(RESERVATION_AMOUNT) * VALUE_PER_GAS;
+    let state = read_state(&tamagotchi);
+    assert_eq!(state.sponsored_value, 2 * VALUE - paid);
+    assert_eq!(state.sponsors[&SPONSOR.into()].value, 2 * VALUE - paid);
+    assert_eq!(state.sponsors[&OWNER.into()].gas, 2 * RESERVATION_AMOUNT);
+}
@@ -0,0 +1,169 @@
+use crate::{Purchase, TamagotchiState, TmgError};
+use gstd::{exec, msg, prelude::*, ActorId};
+use store_io::{AttributeId, Price, StoreAction, StoreEvent};
+
+/// Care the tamagotchi gives itself on a self-scheduled `CheckState`.
+///
+/// A care action with a consumable is only given if the consumable can be bought from the
+/// store, paid by the owner: the tamagotchi takes the list price from the tokens the owner
+/// approved it to spend, and pays the store it approved with `ApproveTokens`.
+#[derive(Encode, Decode, TypeInfo, Debug, Clone, Copy, PartialEq, Eq)]
+#[codec(crate = gstd::codec)]
+#[scale_info(crate = gstd::scale_info)]
+pub struct CarePolicy {
+    pub store_id: ActorId,
+    /// Attribute bought before every feeding.
+    pub food: Option<AttributeId>,
+    /// Attribute bought before every entertainment.
+    pub toy: Option<AttributeId>,
+    /// Attribute bought before every sleep.
+    pub bed: Option<AttributeId>,
+    /// Most tokens spent on consumables in one period, counted at their list prices.
+    pub spending_cap: Price,
+    /// Length of a spending period in blocks.
+    pub period: u32,
+}
+
+impl CarePolicy {
+    pub fn is_valid(&self) -> bool {
+        self.period > 0
+    }
+}
+
+#[derive(Encode, Decode, TypeInfo, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[codec(crate = gstd::codec)]
+#[scale_info(crate = gstd::scale_info)]
+pub struct CareSpending {
+    pub period_start: u32,
+    pub spent: Price,
+}
+
+#[derive(Encode, Decode, TypeInfo, Debug, Clone, Copy, PartialEq, Eq)]
+#[codec(crate = gstd::codec)]
+#[scale_info(crate = gstd::scale_info)]
+pub enum Care {
+    Feed,
+    Entertain,
+    Sleep,
+}
+
+impl TamagotchiState {
+    /// Turns the auto-care on, off or changes it, and starts a new spending period.
+    pub fn set_care_policy(&mut self, policy: Option<CarePolicy>) -> Result<(), TmgError> {
+        self.ensure_owner()?;
+        if policy.is_some_and(|policy| !policy.is_valid()) {
+            return Err(TmgError::InvalidCarePolicy);
+        }
+
+        self.care_policy = policy;
+        self.care_spending = CareSpending {
+            period_start: exec::block_height(),
+            spent: 0,
+        };
+        Ok(())
+    }
+
+    /// Gives the care for every need of the tamagotchi, in the order `CheckState` reports
+    /// needs.
+    ///
+    /// Returns the care given, a need is left if the consumable for its care couldn't be
+    /// bought.
+    pub async fn auto_care(&mut self) -> Vec<Care> {
+        let mut given = Vec::new();
+        let Some(policy) = self.care_policy else {
+            return given;
+        };
+        if self.is_dead() {
+            return given;
+        }
+
+        self.update_stats();
+        let needs = [
+            (self.is_hungry(), Care::Feed, policy.food),
+            (self.is_bored(), Care::Entertain, policy.toy),
+            (self.is_tired(), Care::Sleep, policy.bed),
+        ];
+        for (needed, care, consumable) in needs {
+            if !needed {
+                continue;
+            }
+            if let Some(attribute_id) = consumable {
+                if !self.buy_consumable(&policy, attribute_id).await {
+                    continue;
+                }
+            }
+
+            match care {
+                Care::Feed => self.feed(),
+                Care::Entertain => self.entertain(),
+                Care::Sleep => self.sleep(),
+            };
+            given.push(care);
+        }
+        given
+    }
+
+    async fn buy_consumable(&mut self, policy: &CarePolicy, attribute_id: AttributeId) -> bool {
+        // a pending purchase is left for the owner to complete
+        if self.pending_purchase.is_some() {
+            return false;
+        }
+
+        let reply = msg::send_for_reply_as::<_, StoreEvent>(
+            policy.store_id,
+            StoreAction::GetAttribute { attribute_id },
+            0,
+            0,
+        )
+        .expect("Error in sending a message `StoreAction::GetAttribute`")
+        .await;
+        let price = match reply {
+            Ok(StoreEvent::Attribute {
+                info: Some(info), ..
+            }) if !info.delisted => info.price,
+            _ => return false,
+        };
+
+        let now = exec::block_height();
+        if now.saturating_sub(self.care_spending.period_start) >= policy.period {
+            self.care_spending = CareSpending {
+                period_start: now,
+                spent: 0,
+            };
+        }
+        let spent = self.care_spending.spent.saturating_add(price);
+        if spent > policy.spending_cap {
+            return false;
+        }
+
+        let owner = self.owner;
+        if self
+            .transfer_tokens(&owner, &exec::program_id(), price)
+            .await
+            .is_err()
+        {
+            return false;
+        }
+        self.care_spending.spent = spent;
+
+        match self
+            .purchase(&policy.store_id, attribute_id, None, None)
+            .await
+        {
+            Ok(Purchase::Bought(_)) => true,
+            Ok(Purchase::Failed) => {
+                // the store didn't take the tokens, they go back to the owner
+                if self
+                    .transfer_tokens(&exec::program_id(), &owner, price)
+                    .await
+                    .is_ok()
+                {
+                    self.care_spending.spent = self.care_spending.spent.saturating_sub(price);
+                }
+                false
+            }
+            // the tokens stay with the tamagotchi for the purchase the owner completes
+            _ => false,
+        }
+    }
+}
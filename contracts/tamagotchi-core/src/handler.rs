@@ -0,0 +1,322 @@
+use crate::{
+    BundlePurchase, Purchase, Scheduler, Tamagotchi, TamagotchiState, TmgAction, TmgEvent,
+};
+use gstd::{debug, exec, msg, prelude::*};
+
+/// Handles a message sent to a tamagotchi program and replies to it.
+///
+/// The `CheckState` messages the program sends itself run the loop of the scheduler instead.
+pub async fn handle(tamagotchi: &mut TamagotchiState, scheduler: &mut Scheduler) {
+    if tamagotchi.update_lifecycle() {
+        scheduler.stop();
+        notify(tamagotchi, TmgEvent::Died);
+    }
+
+    let tmg_action: TmgAction = msg::load().expect("Error loading TmgAction");
+    let mut reply_value = 0;
+    let mut sponsored = false;
+    let result = match tmg_action {
+        TmgAction::Name => Ok(TmgEvent::Name(tamagotchi.name.to_string())),
+        TmgAction::Age => Ok(TmgEvent::Age(tamagotchi.age())),
+        TmgAction::Feed => tamagotchi.ensure_alive().map(|()| {
+            tamagotchi.feed();
+            debug!("Feed action {:?}", &tamagotchi.fed.to_string());
+            TmgEvent::Fed
+        }),
+        TmgAction::Entertain => tamagotchi.ensure_alive().map(|()| {
+            tamagotchi.entertain();
+            debug!("Entertain action {:?}", &tamagotchi.entertained.to_string());
+            TmgEvent::Entertained
+        }),
+        TmgAction::Sleep => tamagotchi.ensure_alive().map(|()| {
+            tamagotchi.sleep();
+            debug!("Sleep action {:?}", &tamagotchi.slept.to_string());
+            TmgEvent::Slept
+        }),
+        TmgAction::Transfer(new_owner) => tamagotchi
+            .transfer(new_owner)
+            .map(|()| TmgEvent::Transferred(new_owner)),
+        TmgAction::Approve(account) => tamagotchi
+            .approve(account)
+            .map(|()| TmgEvent::Approved(account)),
+        TmgAction::RevokeApproval => tamagotchi
+            .revoke_approval()
+            .map(|()| TmgEvent::ApprovalRevoked),
+        TmgAction::SetFTokenContract(contract) => tamagotchi
+            .set_ft_contract(contract)
+            .map(|()| TmgEvent::FTokenContractSet),
+        TmgAction::ApproveTokens { account, amount } => tamagotchi
+            .approve_tokens(&account, amount)
+            .await
+            .map(|approved| {
+                if approved {
+                    TmgEvent::TokensApproved { account, amount }
+                } else {
+                    TmgEvent::ApprovalError
+                }
+            }),
+        TmgAction::BuyAttribute {
+            store_id,
+            attribute_id,
+            coupon,
+        } => tamagotchi
+            .buy_attribute(&store_id, attribute_id, coupon)
+            .await
+            .map(purchase_event),
+        TmgAction::GiftAttribute {
+            store_id,
+            attribute_id,
+            recipient,
+            coupon,
+        } => tamagotchi
+            .gift_attribute(&store_id, attribute_id, recipient, coupon)
+            .await
+            .map(purchase_event),
+        TmgAction::CompletePrevPurchase => tamagotchi
+            .complete_prev_purchase()
+            .await
+            .map(purchase_event),
+        TmgAction::RetryPurchase(store_id) => tamagotchi
+            .retry_purchase(&store_id)
+            .await
+            .map(purchase_event),
+        TmgAction::TransferAttribute { attribute_id, to } => tamagotchi
+            .transfer_attribute(to, attribute_id)
+            .await
+            .map(|()| TmgEvent::AttributeTransferred { attribute_id, to }),
+        TmgAction::SyncInventory(store_id) => tamagotchi
+            .sync_inventory(&store_id)
+            .await
+            .map(|()| TmgEvent::InventorySynced),
+        TmgAction::ListAttribute {
+            marketplace_id,
+            attribute_id,
+            price,
+        } => tamagotchi
+            .list_attribute(&marketplace_id, attribute_id, price)
+            .await
+            .map(|()| TmgEvent::AttributeListed {
+                attribute_id,
+                price,
+            }),
+        TmgAction::CancelListing {
+            marketplace_id,
+            attribute_id,
+        } => tamagotchi
+            .cancel_listing(&marketplace_id, attribute_id)
+            .await
+            .map(|()| TmgEvent::ListingCancelled(attribute_id)),
+        TmgAction::BuyListedAttribute {
+            marketplace_id,
+            seller,
+            attribute_id,
+        } => tamagotchi
+            .buy_listed_attribute(&marketplace_id, &seller, attribute_id)
+            .await
+            .map(|bought| {
+                if bought {
+                    TmgEvent::ListedAttributeBought {
+                        seller,
+                        attribute_id,
+                    }
+                } else {
+                    TmgEvent::ErrorDuringPurchase
+                }
+            }),
+        TmgAction::BuyBundle {
+            store_id,
+            bundle_id,
+        } => tamagotchi
+            .buy_bundle(&store_id, bundle_id)
+            .await
+            .map(|purchase| match purchase {
+                BundlePurchase::Bought(bundle_id) => TmgEvent::BundleBought(bundle_id),
+                BundlePurchase::CompletePrevious(bundle_id) => {
+                    TmgEvent::CompletePrevBundle(bundle_id)
+                }
+                BundlePurchase::Failed => TmgEvent::ErrorDuringPurchase,
+            }),
+        TmgAction::Equip(attribute_id) => tamagotchi
+            .equip(attribute_id)
+            .await
+            .map(|slot| TmgEvent::Equipped { attribute_id, slot }),
+        TmgAction::Unequip(slot) => tamagotchi
+            .unequip(slot)
+            .map(|attribute_id| TmgEvent::Unequipped { attribute_id, slot }),
+        TmgAction::ReturnAttribute(attribute_id) => tamagotchi
+            .return_attribute(attribute_id)
+            .await
+            .map(|()| TmgEvent::AttributeReturned(attribute_id)),
+        TmgAction::UpdateConfig(config) => tamagotchi
+            .update_config(config)
+            .map(|()| TmgEvent::ConfigUpdated(config)),
+        TmgAction::Revive => tamagotchi.revive().await.map(|()| TmgEvent::Revived),
+        TmgAction::CheckState => {
+            let payload;
+
+            tamagotchi.update_stats();
+
+            if tamagotchi.is_hungry() {
+                payload = TmgEvent::FeedMe;
+            } else if tamagotchi.is_bored() {
+                payload = TmgEvent::PlayWithMe;
+            } else if tamagotchi.is_tired() {
+                payload = TmgEvent::WantToSleep;
+            } else {
+                payload = TmgEvent::NothingToDo;
+            }
+
+            if exec::program_id() != msg::source() {
+                msg::reply(payload, msg::value()).expect("Error in reply");
+                return;
+            }
+
+            if !scheduler.is_running() {
+                return;
+            }
+
+            if !scheduler.schedule(TmgAction::CheckState) {
+                notify(tamagotchi, TmgEvent::MakeReservation);
+            } else if let Some(blocks) = scheduler.low_runway() {
+                notify(tamagotchi, TmgEvent::LowGasRunway { blocks });
+            }
+
+            if payload != TmgEvent::NothingToDo {
+                for care in tamagotchi.auto_care().await {
+                    notify(tamagotchi, TmgEvent::CareGiven(care));
+                }
+                // every need the tamagotchi couldn't care for itself goes in one notification
+                if tamagotchi.has_needs() {
+                    notify(tamagotchi, needs(tamagotchi));
+                }
+            }
+            return;
+        }
+        TmgAction::CheckNeeds => {
+            tamagotchi.update_stats();
+            Ok(needs(tamagotchi))
+        }
+        TmgAction::ReserveGas {
+            reservation_amount,
+            duration,
+        } => scheduler.sponsor(reservation_amount, duration).map(|_| {
+            sponsored = true;
+            reply_value = scheduler.pay_for_reservation(reservation_amount, duration);
+            scheduler.start(TmgAction::CheckState);
+            TmgEvent::GasReserved
+        }),
+        TmgAction::Sponsor {
+            reservation_amount,
+            duration,
+        } => scheduler
+            .sponsor(reservation_amount, duration)
+            .map(|sponsorship| {
+                sponsored = true;
+                if sponsorship.gas > 0 {
+                    scheduler.start(TmgAction::CheckState);
+                }
+                TmgEvent::Sponsored {
+                    sponsor: msg::source(),
+                    gas: sponsorship.gas,
+                    value: sponsorship.value,
+                }
+            }),
+        TmgAction::WithdrawSponsorship(amount) => tamagotchi
+            .ensure_owner()
+            .and_then(|()| scheduler.withdraw(amount))
+            .map(|()| {
+                reply_value = amount;
+                TmgEvent::SponsorshipWithdrawn(amount)
+            }),
+        TmgAction::UnreserveGas => tamagotchi
+            .ensure_owner()
+            .map(|()| TmgEvent::GasUnreserved(scheduler.stop())),
+        TmgAction::SetCarePolicy(policy) => tamagotchi
+            .set_care_policy(policy)
+            .map(|()| TmgEvent::CarePolicySet(policy)),
+        TmgAction::SetCheckInterval(interval) => tamagotchi
+            .ensure_owner()
+            .and_then(|()| scheduler.set_interval(interval))
+            .map(|()| TmgEvent::CheckIntervalSet(interval)),
+        TmgAction::Subscribe(account) => tamagotchi
+            .subscribe(account)
+            .map(|()| TmgEvent::Subscribed(account)),
+        TmgAction::Unsubscribe(account) => tamagotchi
+            .unsubscribe(account)
+            .map(|()| TmgEvent::Unsubscribed(account)),
+    };
+
+    // only a sponsorship keeps the value attached to it, any other action gives it back
+    if !sponsored {
+        reply_value = reply_value.saturating_add(msg::value());
+    }
+    msg::reply(result.unwrap_or_else(TmgEvent::Error), reply_value)
+        .expect("Error in sending reply");
+}
+
+/// Returns the state of a tamagotchi program with the stats at the current block.
+pub fn state(tamagotchi: &mut TamagotchiState, scheduler: &Scheduler) -> Tamagotchi {
+    tamagotchi.update_stats();
+    tamagotchi.update_lifecycle();
+    Tamagotchi {
+        name: tamagotchi.name.clone(),
+        date_of_birth: tamagotchi.date_of_birth,
+        owner: tamagotchi.owner,
+        fed: tamagotchi.fed,
+        fed_block: tamagotchi.fed_block,
+        entertained: tamagotchi.entertained,
+        entertained_block: tamagotchi.entertained_block,
+        slept: tamagotchi.slept,
+        slept_block: tamagotchi.slept_block,
+        approved_account: tamagotchi.approved_account,
+        ft_contract_id: tamagotchi.ft_contract_id,
+        transaction_id: tamagotchi.transaction_id,
+        approve_transaction: tamagotchi.approve_transaction,
+        pending_purchase: tamagotchi.pending_purchase,
+        inventory: tamagotchi.inventory.clone(),
+        equipment: tamagotchi.equipment.clone(),
+        listed: tamagotchi.listed.clone(),
+        reservations: scheduler.reservations().to_vec(),
+        runway: scheduler.runway(),
+        check_interval: scheduler.interval(),
+        subscribers: tamagotchi.subscribers.clone(),
+        sponsors: scheduler.sponsors().clone(),
+        sponsored_value: scheduler.sponsored_value(),
+        config: tamagotchi.config,
+        lifecycle: tamagotchi.lifecycle,
+        care_policy: tamagotchi.care_policy,
+        care_spending: tamagotchi.care_spending,
+    }
+}
+
+fn purchase_event(purchase: Purchase) -> TmgEvent {
+    match purchase {
+        Purchase::Bought(attribute_id) => TmgEvent::AttributeBought(attribute_id),
+        Purchase::Gifted {
+            attribute_id,
+            recipient,
+        } => TmgEvent::AttributeGifted {
+            attribute_id,
+            recipient,
+        },
+        Purchase::CompletePrevious(attribute_id) => TmgEvent::CompletePrevPurchase(attribute_id),
+        Purchase::Failed => TmgEvent::ErrorDuringPurchase,
+    }
+}
+
+/// Reports every need of the tamagotchi along with its stats.
+fn needs(tamagotchi: &TamagotchiState) -> TmgEvent {
+    TmgEvent::Needs {
+        hungry: tamagotchi.is_hungry(),
+        bored: tamagotchi.is_bored(),
+        tired: tamagotchi.is_tired(),
+        values: tamagotchi.stat_values(),
+    }
+}
+
+/// Sends a notification of the `CheckState` loop to the owner and the subscribers.
+fn notify(tamagotchi: &TamagotchiState, event: TmgEvent) {
+    for recipient in tamagotchi.recipients() {
+        msg::send(recipient, &event, 0).expect("Error in sending a notification");
+    }
+}
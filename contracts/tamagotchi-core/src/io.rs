@@ -0,0 +1,223 @@
+use crate::{
+    Care, CarePolicy, CareSpending, Lifecycle, Reservation, Sponsorship, StatConfig, StatValues,
+    TmgError,
+};
+use gstd::{
+    collections::{BTreeMap, BTreeSet},
+    prelude::*,
+    ActorId,
+};
+use store_io::{AttributeId, BundleId, Slot, TransactionId};
+
+#[derive(Default, Encode, Decode, TypeInfo)]
+#[codec(crate = gstd::codec)]
+#[scale_info(crate = gstd::scale_info)]
+pub struct Tamagotchi {
+    pub name: String,
+    pub date_of_birth: u64,
+    pub owner: ActorId,
+    pub fed: u32,
+    pub fed_block: u32,
+    pub entertained: u32,
+    pub entertained_block: u32,
+    pub slept: u32,
+    pub slept_block: u32,
+    pub approved_account: Option<ActorId>,
+    pub ft_contract_id: Option<ActorId>,
+    pub transaction_id: u64,
+    pub approve_transaction: Option<(TransactionId, ActorId, u128)>,
+    pub pending_purchase: Option<(ActorId, AttributeId, Option<ActorId>)>,
+    pub inventory: BTreeMap<AttributeId, ActorId>,
+    pub equipment: BTreeMap<Slot, AttributeId>,
+    /// Attributes up for sale and the marketplaces they are listed on.
+    pub listed: BTreeMap<AttributeId, ActorId>,
+    /// Reservations paying for the `CheckState` loop, the one expiring first is used next.
+    pub reservations: Vec<Reservation>,
+    /// Blocks the `CheckState` loop can keep running with the reservations left.
+    pub runway: u32,
+    /// Blocks between two checks of the `CheckState` loop.
+    pub check_interval: u32,
+    /// Accounts notified by the `CheckState` loop along with the owner.
+    pub subscribers: BTreeSet<ActorId>,
+    /// Gas and value every account has given for the `CheckState` loop.
+    pub sponsors: BTreeMap<ActorId, Sponsorship>,
+    /// Sponsored value that hasn't paid for reservations, the owner can withdraw it.
+    pub sponsored_value: u128,
+    pub config: StatConfig,
+    pub lifecycle: Lifecycle,
+    pub care_policy: Option<CarePolicy>,
+    /// Tokens spent on consumables in the current spending period.
+    pub care_spending: CareSpending,
+}
+
+#[derive(Encode, Decode, TypeInfo)]
+#[codec(crate = gstd::codec)]
+#[scale_info(crate = gstd::scale_info)]
+pub enum TmgAction {
+    Name,
+    Age,
+    Feed,
+    Entertain,
+    Sleep,
+    Transfer(ActorId),
+    Approve(ActorId),
+    RevokeApproval,
+    SetFTokenContract(ActorId),
+    ApproveTokens {
+        account: ActorId,
+        amount: u128,
+    },
+    BuyAttribute {
+        store_id: ActorId,
+        attribute_id: AttributeId,
+        /// Single-use store coupon code.
+        coupon: Option<String>,
+    },
+    GiftAttribute {
+        store_id: ActorId,
+        attribute_id: AttributeId,
+        recipient: ActorId,
+        coupon: Option<String>,
+    },
+    CompletePrevPurchase,
+    /// Asks the store to complete its pending purchase for this tamagotchi.
+    RetryPurchase(ActorId),
+    TransferAttribute {
+        attribute_id: AttributeId,
+        to: ActorId,
+    },
+    SyncInventory(ActorId),
+    ListAttribute {
+        marketplace_id: ActorId,
+        attribute_id: AttributeId,
+        price: u128,
+    },
+    CancelListing {
+        marketplace_id: ActorId,
+        attribute_id: AttributeId,
+    },
+    BuyListedAttribute {
+        marketplace_id: ActorId,
+        seller: ActorId,
+        attribute_id: AttributeId,
+    },
+    BuyBundle {
+        store_id: ActorId,
+        bundle_id: BundleId,
+    },
+    Equip(AttributeId),
+    Unequip(Slot),
+    ReturnAttribute(AttributeId),
+    UpdateConfig(StatConfig),
+    Revive,
+    CheckState,
+    /// Reports every unmet need at once, unlike `CheckState`.
+    CheckNeeds,
+    /// Reserves gas for the `CheckState` loop and starts it. A reservation that lasts until
+    /// the next message of the loop is paid back out of the sponsored value, with the reply.
+    ReserveGas {
+        reservation_amount: u64,
+        duration: u32,
+    },
+    /// Reserves gas like `ReserveGas`, the value attached to the message pays for the gas
+    /// reserved later with `ReserveGas`. Anyone can sponsor a tamagotchi.
+    Sponsor {
+        reservation_amount: u64,
+        duration: u32,
+    },
+    /// Sends the owner part of the sponsored value, taken from every sponsor in proportion to
+    /// what they have left.
+    WithdrawSponsorship(u128),
+    /// Stops the `CheckState` loop and unreserves the gas left.
+    UnreserveGas,
+    /// Lets the tamagotchi care for itself on the `CheckState` loop, `None` turns it off.
+    SetCarePolicy(Option<CarePolicy>),
+    SetCheckInterval(u32),
+    Subscribe(ActorId),
+    Unsubscribe(ActorId),
+}
+
+#[derive(Encode, Decode, TypeInfo, Eq, PartialEq)]
+#[codec(crate = gstd::codec)]
+#[scale_info(crate = gstd::scale_info)]
+pub enum TmgEvent {
+    Name(String),
+    Age(u64),
+    Fed,
+    Entertained,
+    Slept,
+    Transferred(ActorId),
+    Approved(ActorId),
+    ApprovalRevoked,
+    FTokenContractSet,
+    TokensApproved {
+        account: ActorId,
+        amount: u128,
+    },
+    ApprovalError,
+    AttributeBought(AttributeId),
+    CompletePrevPurchase(AttributeId),
+    ErrorDuringPurchase,
+    AttributeGifted {
+        attribute_id: AttributeId,
+        recipient: ActorId,
+    },
+    AttributeTransferred {
+        attribute_id: AttributeId,
+        to: ActorId,
+    },
+    InventorySynced,
+    AttributeListed {
+        attribute_id: AttributeId,
+        price: u128,
+    },
+    ListingCancelled(AttributeId),
+    ListedAttributeBought {
+        seller: ActorId,
+        attribute_id: AttributeId,
+    },
+    BundleBought(BundleId),
+    CompletePrevBundle(BundleId),
+    Equipped {
+        attribute_id: AttributeId,
+        slot: Slot,
+    },
+    Unequipped {
+        attribute_id: AttributeId,
+        slot: Slot,
+    },
+    AttributeReturned(AttributeId),
+    ConfigUpdated(StatConfig),
+    Died,
+    Revived,
+    Error(TmgError),
+    FeedMe,
+    PlayWithMe,
+    WantToSleep,
+    NothingToDo,
+    Needs {
+        hungry: bool,
+        bored: bool,
+        tired: bool,
+        values: StatValues,
+    },
+    MakeReservation,
+    /// Sent to the owner when the `CheckState` loop is about to run out of reservations.
+    LowGasRunway {
+        blocks: u32,
+    },
+    GasReserved,
+    Sponsored {
+        sponsor: ActorId,
+        gas: u64,
+        value: u128,
+    },
+    SponsorshipWithdrawn(u128),
+    GasUnreserved(u32),
+    CarePolicySet(Option<CarePolicy>),
+    /// Sent to the owner when the tamagotchi cared for itself.
+    CareGiven(Care),
+    CheckIntervalSet(u32),
+    Subscribed(ActorId),
+    Unsubscribed(ActorId),
+}
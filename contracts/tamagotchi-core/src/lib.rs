@@ -0,0 +1,182 @@
+#![no_std]
+
+use gstd::{
+    collections::{BTreeMap, BTreeSet},
+    exec, msg,
+    prelude::*,
+    ActorId,
+};
+use store_io::{AttrEffects, AttributeId, Slot, TransactionId};
+
+mod care;
+mod handler;
+mod io;
+mod marketplace;
+mod scheduler;
+mod shop;
+mod stats;
+mod tokens;
+
+pub use care::{Care, CarePolicy, CareSpending};
+pub use handler::{handle, state};
+pub use io::{Tamagotchi, TmgAction, TmgEvent};
+pub use scheduler::{
+    Reservation, Scheduler, Sponsorship, DEFAULT_CHECK_INTERVAL, LOW_RUNWAY_RESERVATIONS,
+    SPONSORED_VALUE_PER_GAS,
+};
+pub use shop::{BundlePurchase, Purchase};
+pub use stats::{current_stat, Lifecycle, StatConfig, StatValues};
+
+/// Most accounts that can subscribe to the notifications of a tamagotchi.
+pub const MAX_SUBSCRIBERS: usize = 10;
+
+#[derive(Encode, Decode, TypeInfo, Debug, Clone, Copy, PartialEq, Eq)]
+#[codec(crate = gstd::codec)]
+#[scale_info(crate = gstd::scale_info)]
+pub enum TmgError {
+    NotOwner,
+    NotApproved,
+    Dead,
+    NotDead,
+    InvalidConfig,
+    FtContractNotSet,
+    FtTransferFailed,
+    StoreRejected,
+    NoPendingPurchase,
+    NotInInventory,
+    NotEquippable,
+    SlotEmpty,
+    ReturnWindowClosed,
+    ReservationFailed,
+    InvalidCoupon,
+    AttributeEquipped,
+    AttributeListed,
+    MarketplaceRejected,
+    InvalidCarePolicy,
+    InvalidCheckInterval,
+    NotSubscribed,
+    TooManySubscribers,
+    NothingSponsored,
+    NotEnoughSponsoredValue,
+}
+
+/// State shared by every lesson contract.
+///
+/// Each stat keeps the value it had at `*_block`, the current value is that
+/// value minus the decay accumulated since then. A stat that has dropped to
+/// zero keeps the block at which it happened.
+#[derive(Default, Debug)]
+pub struct TamagotchiState {
+    pub name: String,
+    pub date_of_birth: u64,
+    pub owner: ActorId,
+    pub fed: u32,
+    pub fed_block: u32,
+    pub entertained: u32,
+    pub entertained_block: u32,
+    pub slept: u32,
+    pub slept_block: u32,
+    pub approved_account: Option<ActorId>,
+    pub ft_contract_id: Option<ActorId>,
+    pub transaction_id: TransactionId,
+    pub approve_transaction: Option<(TransactionId, ActorId, u128)>,
+    /// Store and attribute of a purchase the store hasn't completed yet.
+    pub pending_purchase: Option<(ActorId, AttributeId, Option<ActorId>)>,
+    /// Bought attributes and the stores they were bought from.
+    pub inventory: BTreeMap<AttributeId, ActorId>,
+    pub equipment: BTreeMap<Slot, AttributeId>,
+    /// Attributes up for sale and the marketplaces they are listed on.
+    pub listed: BTreeMap<AttributeId, ActorId>,
+    /// Effects of the equipped attributes.
+    pub effects: BTreeMap<Slot, AttrEffects>,
+    pub config: StatConfig,
+    pub lifecycle: Lifecycle,
+    /// Care given on self-scheduled checks, off if `None`.
+    pub care_policy: Option<CarePolicy>,
+    pub care_spending: CareSpending,
+    /// Accounts notified of the needs of the tamagotchi along with the owner.
+    pub subscribers: BTreeSet<ActorId>,
+}
+
+impl TamagotchiState {
+    pub fn new(name: String, owner: ActorId, config: StatConfig) -> Self {
+        assert!(config.is_valid(), "Invalid stat config");
+        let mut tamagotchi = Self {
+            name,
+            date_of_birth: exec::block_timestamp(),
+            owner,
+            config,
+            ..Default::default()
+        };
+        tamagotchi.restore_stats();
+        tamagotchi
+    }
+
+    pub fn is_owner(&self, account: &ActorId) -> bool {
+        self.owner == *account
+    }
+
+    pub fn can_transfer(&self, account: &ActorId) -> bool {
+        self.is_owner(account) || self.approved_account == Some(*account)
+    }
+
+    pub fn ensure_owner(&self) -> Result<(), TmgError> {
+        if !self.is_owner(&msg::source()) {
+            return Err(TmgError::NotOwner);
+        }
+        Ok(())
+    }
+
+    pub fn transfer(&mut self, new_owner: ActorId) -> Result<(), TmgError> {
+        if !self.can_transfer(&msg::source()) {
+            return Err(TmgError::NotApproved);
+        }
+        self.owner = new_owner;
+        // the subscribers were chosen by the previous owner
+        self.subscribers.clear();
+        Ok(())
+    }
+
+    pub fn subscribe(&mut self, account: ActorId) -> Result<(), TmgError> {
+        self.ensure_owner()?;
+        if self.subscribers.len() >= MAX_SUBSCRIBERS && !self.subscribers.contains(&account) {
+            return Err(TmgError::TooManySubscribers);
+        }
+        self.subscribers.insert(account);
+        Ok(())
+    }
+
+    /// Subscribers can unsubscribe themselves, the owner can unsubscribe anyone.
+    pub fn unsubscribe(&mut self, account: ActorId) -> Result<(), TmgError> {
+        if msg::source() != account {
+            self.ensure_owner()?;
+        }
+        if !self.subscribers.remove(&account) {
+            return Err(TmgError::NotSubscribed);
+        }
+        Ok(())
+    }
+
+    /// The owner and the subscribers.
+    pub fn recipients(&self) -> impl Iterator<Item = ActorId> + '_ {
+        core::iter::once(self.owner).chain(self.subscribers.iter().copied())
+    }
+
+    pub fn approve(&mut self, account: ActorId) -> Result<(), TmgError> {
+        self.ensure_owner()?;
+        self.approved_account = Some(account);
+        Ok(())
+    }
+
+    pub fn revoke_approval(&mut self) -> Result<(), TmgError> {
+        self.ensure_owner()?;
+        self.approved_account = None;
+        Ok(())
+    }
+
+    pub fn set_ft_contract(&mut self, ft_contract_id: ActorId) -> Result<(), TmgError> {
+        self.ensure_owner()?;
+        self.ft_contract_id = Some(ft_contract_id);
+        Ok(())
+    }
+}
@@ -0,0 +1,101 @@
+use crate::{TamagotchiState, TmgError};
+use gstd::{msg, ActorId};
+use marketplace_io::{MarketAction, MarketEvent};
+use store_io::AttributeId;
+
+impl TamagotchiState {
+    /// Puts an owned attribute up for sale on a marketplace, it must not be worn.
+    pub async fn list_attribute(
+        &mut self,
+        marketplace_id: &ActorId,
+        attribute_id: AttributeId,
+        price: u128,
+    ) -> Result<(), TmgError> {
+        self.ensure_owner()?;
+        if !self.inventory.contains_key(&attribute_id) {
+            return Err(TmgError::NotInInventory);
+        }
+        if self.equipment.values().any(|id| *id == attribute_id) {
+            return Err(TmgError::AttributeEquipped);
+        }
+
+        let reply = msg::send_for_reply_as::<_, MarketEvent>(
+            *marketplace_id,
+            MarketAction::List {
+                attribute_id,
+                price,
+            },
+            0,
+            0,
+        )
+        .expect("Error in sending a message `MarketAction::List`")
+        .await;
+
+        match reply {
+            Ok(MarketEvent::Listed { .. }) => {
+                self.listed.insert(attribute_id, *marketplace_id);
+                Ok(())
+            }
+            _ => Err(TmgError::MarketplaceRejected),
+        }
+    }
+
+    pub async fn cancel_listing(
+        &mut self,
+        marketplace_id: &ActorId,
+        attribute_id: AttributeId,
+    ) -> Result<(), TmgError> {
+        self.ensure_owner()?;
+
+        let reply = msg::send_for_reply_as::<_, MarketEvent>(
+            *marketplace_id,
+            MarketAction::CancelListing { attribute_id },
+            0,
+            0,
+        )
+        .expect("Error in sending a message `MarketAction::CancelListing`")
+        .await;
+
+        match reply {
+            Ok(MarketEvent::ListingCancelled { .. }) => {
+                self.listed.remove(&attribute_id);
+                Ok(())
+            }
+            _ => Err(TmgError::MarketplaceRejected),
+        }
+    }
+
+    /// Buys an attribute listed by another tamagotchi.
+    ///
+    /// The marketplace takes the price with the token allowance given by `approve_tokens`.
+    /// Returns whether the attribute was bought.
+    pub async fn buy_listed_attribute(
+        &mut self,
+        marketplace_id: &ActorId,
+        seller: &ActorId,
+        attribute_id: AttributeId,
+    ) -> Result<bool, TmgError> {
+        self.ensure_owner()?;
+
+        let reply = msg::send_for_reply_as::<_, MarketEvent>(
+            *marketplace_id,
+            MarketAction::Buy {
+                seller: *seller,
+                attribute_id,
+            },
+            0,
+            0,
+        )
+        .expect("Error in sending a message `MarketAction::Buy`")
+        .await;
+
+        match reply {
+            Ok(MarketEvent::Sold { store_id, .. }) => {
+                self.inventory.insert(attribute_id, store_id);
+                Ok(true)
+            }
+            Ok(MarketEvent::PurchaseFailed { .. }) => Ok(false),
+            _ => Err(TmgError::MarketplaceRejected),
+        }
+    }
+}
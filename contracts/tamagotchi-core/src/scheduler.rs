@@ -0,0 +1,268 @@
+use crate::TmgError;
+use gstd::{collections::BTreeMap, exec, msg, prelude::*, ActorId, ReservationId};
+
+/// Usable reservations left when the owner is asked to reserve more gas.
+pub const LOW_RUNWAY_RESERVATIONS: usize = 2;
+/// Blocks between two messages of the loop unless the owner sets another interval, about a
+/// minute.
+pub const DEFAULT_CHECK_INTERVAL: u32 = 20;
+/// Sponsored value paid back for a unit of gas reserved with `TmgAction::ReserveGas`.
+pub const SPONSORED_VALUE_PER_GAS: u128 = 25;
+
+/// Gas reserved for a delayed message the tamagotchi sends itself.
+#[derive(Encode, Decode, TypeInfo, Debug, Clone, Copy, PartialEq, Eq)]
+#[codec(crate = gstd::codec)]
+#[scale_info(crate = gstd::scale_info)]
+pub struct Reservation {
+    pub id: ReservationId,
+    /// Block height from which the reservation can't be used anymore.
+    pub expires_at: u32,
+}
+
+/// What an account has given to keep the loop running.
+#[derive(Encode, Decode, TypeInfo, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[codec(crate = gstd::codec)]
+#[scale_info(crate = gstd::scale_info)]
+pub struct Sponsorship {
+    /// Gas reserved out of the messages of the account.
+    pub gas: u64,
+    /// Value attached to the messages of the account that hasn't paid for reservations or been
+    /// withdrawn.
+    pub value: u128,
+}
+
+/// Pays for a self-scheduled message loop out of gas reservations.
+///
+/// Reservations are kept sorted by expiry and the one expiring first is used for the next
+/// message, so fewer of them go to waste. Expired ones are discarded before every send.
+#[derive(Debug)]
+pub struct Scheduler {
+    reservations: Vec<Reservation>,
+    /// Blocks between two messages of the loop.
+    interval: u32,
+    running: bool,
+    /// Block height the message already in flight arrives at.
+    next_at: Option<u32>,
+    low_runway_notified: bool,
+    sponsors: BTreeMap<ActorId, Sponsorship>,
+    /// Value given by the sponsors that hasn't paid for reservations or been withdrawn.
+    sponsored_value: u128,
+}
+
+impl Scheduler {
+    pub const fn new() -> Self {
+        Self {
+            reservations: Vec::new(),
+            interval: DEFAULT_CHECK_INTERVAL,
+            running: false,
+            next_at: None,
+            low_runway_notified: false,
+            sponsors: BTreeMap::new(),
+            sponsored_value: 0,
+        }
+    }
+
+    pub fn reservations(&self) -> &[Reservation] {
+        &self.reservations
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.running
+    }
+
+    pub fn sponsors(&self) -> &BTreeMap<ActorId, Sponsorship> {
+        &self.sponsors
+    }
+
+    pub fn sponsored_value(&self) -> u128 {
+        self.sponsored_value
+    }
+
+    pub fn interval(&self) -> u32 {
+        self.interval
+    }
+
+    /// The message already in flight keeps the interval it was sent with.
+    pub fn set_interval(&mut self, interval: u32) -> Result<(), TmgError> {
+        if interval == 0 {
+            return Err(TmgError::InvalidCheckInterval);
+        }
+        self.interval = interval;
+        Ok(())
+    }
+
+    /// Reserves gas out of the current message and records it, along with the value attached
+    /// to the message, in the ledger of the sender.
+    pub fn sponsor(&mut self, amount: u64, duration: u32) -> Result<Sponsorship, TmgError> {
+        let value = msg::value();
+        if amount == 0 && value == 0 {
+            return Err(TmgError::NothingSponsored);
+        }
+        if amount > 0 {
+            self.reserve(amount, duration)?;
+        }
+
+        let sponsorship = self.sponsors.entry(msg::source()).or_default();
+        sponsorship.gas = sponsorship.gas.saturating_add(amount);
+        sponsorship.value = sponsorship.value.saturating_add(value);
+        self.sponsored_value = self.sponsored_value.saturating_add(value);
+        Ok(Sponsorship { gas: amount, value })
+    }
+
+    /// Takes part of the sponsored value out, the caller sends it.
+    pub fn withdraw(&mut self, amount: u128) -> Result<(), TmgError> {
+        if amount > self.sponsored_value {
+            return Err(TmgError::NotEnoughSponsoredValue);
+        }
+        self.take(amount);
+        Ok(())
+    }
+
+    /// Returns the sponsored value that pays for `amount` of gas just reserved, the caller
+    /// sends it to whoever reserved the gas.
+    ///
+    /// A reservation that expires before the next message of the loop isn't paid for.
+    pub fn pay_for_reservation(&mut self, amount: u64, duration: u32) -> u128 {
+        if duration < self.interval {
+            return 0;
+        }
+        let value = u128::from(amount)
+            .saturating_mul(SPONSORED_VALUE_PER_GAS)
+            .min(self.sponsored_value);
+        self.take(value);
+        value
+    }
+
+    /// Takes value out of the ledger in proportion to what each sponsor has left.
+    fn take(&mut self, amount: u128) {
+        let total = self.sponsored_value;
+        if total == 0 {
+            return;
+        }
+        self.sponsored_value -= amount;
+
+        let mut left = amount;
+        for sponsorship in self.sponsors.values_mut() {
+            let taken = share(sponsorship.value, amount, total).min(left);
+            sponsorship.value -= taken;
+            left -= taken;
+        }
+        // the shares are rounded down, what they leave is taken from any sponsor with value
+        for sponsorship in self.sponsors.values_mut() {
+            if left == 0 {
+                break;
+            }
+            let taken = left.min(sponsorship.value);
+            sponsorship.value -= taken;
+            left -= taken;
+        }
+    }
+
+    fn reserve(&mut self, amount: u64, duration: u32) -> Result<(), TmgError> {
+        let id =
+            ReservationId::reserve(amount, duration).map_err(|_| TmgError::ReservationFailed)?;
+        let expires_at = exec::block_height().saturating_add(duration);
+        let index = self
+            .reservations
+            .partition_point(|reservation| reservation.expires_at <= expires_at);
+        self.reservations
+            .insert(index, Reservation { id, expires_at });
+
+        if self.reservations.len() > LOW_RUNWAY_RESERVATIONS {
+            self.low_runway_notified = false;
+        }
+        Ok(())
+    }
+
+    /// Starts the loop unless a message of it is still on its way.
+    pub fn start<T: Encode>(&mut self, payload: T) -> bool {
+        self.running = true;
+        if self
+            .next_at
+            .is_some_and(|next_at| next_at > exec::block_height())
+        {
+            return true;
+        }
+        self.schedule(payload)
+    }
+
+    /// Sends the next message of the loop to the program itself after the interval.
+    ///
+    /// Returns `false` and stops the loop if there's no usable reservation left.
+    pub fn schedule<T: Encode>(&mut self, payload: T) -> bool {
+        self.next_at = None;
+        if !self.running {
+            return false;
+        }
+
+        self.discard_expired();
+        if self.reservations.is_empty() {
+            self.running = false;
+            return false;
+        }
+
+        let Reservation { id, .. } = self.reservations.remove(0);
+        msg::send_delayed_from_reservation(id, exec::program_id(), payload, 0, self.interval)
+            .expect("Error sending message from reservation");
+        self.next_at = Some(exec::block_height().saturating_add(self.interval));
+        true
+    }
+
+    /// Stops the loop and gives the gas of the remaining reservations back.
+    ///
+    /// Returns how many reservations were unreserved.
+    pub fn stop(&mut self) -> u32 {
+        self.running = false;
+        self.discard_expired();
+        let mut unreserved = 0;
+        for Reservation { id, .. } in core::mem::take(&mut self.reservations) {
+            if id.unreserve().is_ok() {
+                unreserved += 1;
+            }
+        }
+        unreserved
+    }
+
+    /// Blocks the loop can keep running with the reservations it holds.
+    pub fn runway(&self) -> u32 {
+        let delay = self.interval;
+        let now = exec::block_height();
+        let mut usable: u32 = 0;
+        for reservation in &self.reservations {
+            let used_at = now.saturating_add(delay.saturating_mul(usable));
+            if reservation.expires_at > used_at {
+                usable += 1;
+            }
+        }
+        usable.saturating_mul(delay)
+    }
+
+    /// Returns the runway the first time it falls to `LOW_RUNWAY_RESERVATIONS` messages, so
+    /// the owner is warned only once until more gas is reserved.
+    pub fn low_runway(&mut self) -> Option<u32> {
+        let runway = self.runway();
+        if self.low_runway_notified
+            || runway > self.interval.saturating_mul(LOW_RUNWAY_RESERVATIONS as u32)
+        {
+            return None;
+        }
+        self.low_runway_notified = true;
+        Some(runway)
+    }
+
+    fn discard_expired(&mut self) {
+        let now = exec::block_height();
+        self.reservations
+            .retain(|reservation| reservation.expires_at > now);
+    }
+}
+
+/// `value * amount / total` rounded down, with `value` and `amount` at most `total`.
+///
+/// The low bits of `value` and `total` are dropped when the product doesn't fit, which keeps
+/// the share close to exact.
+fn share(value: u128, amount: u128, total: u128) -> u128 {
+    let bits = (u128::BITS - value.leading_zeros()) + (u128::BITS - amount.leading_zeros());
+    let shift = bits.saturating_sub(u128::BITS);
+    ((value >> shift) * amount / (total >> shift).max(1)).min(value)
+}
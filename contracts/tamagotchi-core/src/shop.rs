@@ -0,0 +1,352 @@
+use crate::{TamagotchiState, TmgError};
+use gstd::{exec, msg, prelude::*, ActorId};
+use store_io::{AttributeId, BundleId, Slot, StoreAction, StoreEvent};
+
+/// Outcome of a purchase request sent to the attribute store.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Purchase {
+    Bought(AttributeId),
+    Gifted {
+        attribute_id: AttributeId,
+        recipient: ActorId,
+    },
+    CompletePrevious(AttributeId),
+    Failed,
+}
+
+/// Outcome of a bundle purchase request sent to the attribute store.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BundlePurchase {
+    Bought(BundleId),
+    CompletePrevious(BundleId),
+    Failed,
+}
+
+impl TamagotchiState {
+    pub async fn buy_attribute(
+        &mut self,
+        store_id: &ActorId,
+        attribute_id: AttributeId,
+        coupon: Option<String>,
+    ) -> Result<Purchase, TmgError> {
+        self.ensure_owner()?;
+        self.purchase(store_id, attribute_id, None, coupon).await
+    }
+
+    /// Buys an attribute that the store gives to another tamagotchi.
+    pub async fn gift_attribute(
+        &mut self,
+        store_id: &ActorId,
+        attribute_id: AttributeId,
+        recipient: ActorId,
+        coupon: Option<String>,
+    ) -> Result<Purchase, TmgError> {
+        self.ensure_owner()?;
+        self.purchase(store_id, attribute_id, Some(recipient), coupon)
+            .await
+    }
+
+    /// Finishes the purchase the store asked to complete before accepting a new one.
+    pub async fn complete_prev_purchase(&mut self) -> Result<Purchase, TmgError> {
+        self.ensure_owner()?;
+        let (store_id, attribute_id, recipient) =
+            self.pending_purchase.ok_or(TmgError::NoPendingPurchase)?;
+        // the store keeps the price of a pending purchase, the coupon isn't needed again
+        self.purchase(&store_id, attribute_id, recipient, None)
+            .await
+    }
+
+    async fn purchase(
+        &mut self,
+        store_id: &ActorId,
+        attribute_id: AttributeId,
+        recipient: Option<ActorId>,
+        coupon: Option<String>,
+    ) -> Result<Purchase, TmgError> {
+        self.pending_purchase = Some((*store_id, attribute_id, recipient));
+
+        let action = match recipient {
+            Some(recipient) => StoreAction::BuyAttributeFor {
+                attribute_id,
+                recipient,
+                coupon,
+            },
+            None => StoreAction::BuyAttribute {
+                attribute_id,
+                coupon,
+            },
+        };
+        let reply = msg::send_for_reply_as::<_, StoreEvent>(*store_id, action, 0, 0)
+            .expect("Error in sending a message `StoreAction::BuyAttribute`")
+            .await;
+
+        match reply {
+            Ok(StoreEvent::AttributeSold { success }) => {
+                self.pending_purchase = None;
+                match (success, recipient) {
+                    (false, _) => Ok(Purchase::Failed),
+                    (true, Some(recipient)) => Ok(Purchase::Gifted {
+                        attribute_id,
+                        recipient,
+                    }),
+                    (true, None) => {
+                        self.inventory.insert(attribute_id, *store_id);
+                        Ok(Purchase::Bought(attribute_id))
+                    }
+                }
+            }
+            Ok(StoreEvent::SoldOut { .. } | StoreEvent::SaleClosed { .. }) => {
+                self.pending_purchase = None;
+                Ok(Purchase::Failed)
+            }
+            Ok(StoreEvent::CompletePrevTx {
+                attribute_id,
+                recipient,
+            }) => {
+                self.pending_purchase = Some((*store_id, attribute_id, recipient));
+                Ok(Purchase::CompletePrevious(attribute_id))
+            }
+            Ok(StoreEvent::InvalidCoupon) => {
+                self.pending_purchase = None;
+                Err(TmgError::InvalidCoupon)
+            }
+            Ok(_) => {
+                self.pending_purchase = None;
+                Err(TmgError::StoreRejected)
+            }
+            // the store may have started the purchase before the reply was lost
+            Err(_) => Err(TmgError::StoreRejected),
+        }
+    }
+
+    /// Asks the store to complete the pending purchase it keeps for this tamagotchi.
+    pub async fn retry_purchase(&mut self, store_id: &ActorId) -> Result<Purchase, TmgError> {
+        self.ensure_owner()?;
+
+        let reply = msg::send_for_reply_as::<_, StoreEvent>(*store_id, StoreAction::RetryTx, 0, 0)
+            .expect("Error in sending a message `StoreAction::RetryTx`")
+            .await;
+
+        let (attribute_id, recipient, success) = match reply {
+            Ok(StoreEvent::TxRetried {
+                attribute_id,
+                recipient,
+                success,
+            }) => (attribute_id, recipient, success),
+            Ok(StoreEvent::NoPendingTx) => return Err(TmgError::NoPendingPurchase),
+            _ => return Err(TmgError::StoreRejected),
+        };
+        if self
+            .pending_purchase
+            .is_some_and(|(pending_store_id, ..)| pending_store_id == *store_id)
+        {
+            self.pending_purchase = None;
+        }
+
+        match (success, recipient) {
+            (false, _) => Ok(Purchase::Failed),
+            (true, Some(recipient)) => Ok(Purchase::Gifted {
+                attribute_id,
+                recipient,
+            }),
+            (true, None) => {
+                self.inventory.insert(attribute_id, *store_id);
+                Ok(Purchase::Bought(attribute_id))
+            }
+        }
+    }
+
+    /// Buys all attributes of a store bundle for the bundle price.
+    pub async fn buy_bundle(
+        &mut self,
+        store_id: &ActorId,
+        bundle_id: BundleId,
+    ) -> Result<BundlePurchase, TmgError> {
+        self.ensure_owner()?;
+
+        let reply = msg::send_for_reply_as::<_, StoreEvent>(
+            *store_id,
+            StoreAction::BuyBundle { bundle_id },
+            0,
+            0,
+        )
+        .expect("Error in sending a message `StoreAction::BuyBundle`")
+        .await;
+
+        match reply {
+            Ok(StoreEvent::BundleSold {
+                attributes,
+                success,
+                ..
+            }) => {
+                if !success {
+                    return Ok(BundlePurchase::Failed);
+                }
+                for attribute_id in attributes {
+                    self.inventory.insert(attribute_id, *store_id);
+                }
+                Ok(BundlePurchase::Bought(bundle_id))
+            }
+            Ok(StoreEvent::SoldOut { .. } | StoreEvent::SaleClosed { .. }) => {
+                Ok(BundlePurchase::Failed)
+            }
+            Ok(StoreEvent::CompletePrevBundleTx { bundle_id }) => {
+                Ok(BundlePurchase::CompletePrevious(bundle_id))
+            }
+            _ => Err(TmgError::StoreRejected),
+        }
+    }
+
+    /// Puts an owned attribute on in the slot given by its store metadata, it must not be
+    /// listed on a marketplace.
+    ///
+    /// Returns the slot, an attribute previously worn there is taken off.
+    pub async fn equip(&mut self, attribute_id: AttributeId) -> Result<Slot, TmgError> {
+        self.ensure_owner()?;
+        let store_id = *self
+            .inventory
+            .get(&attribute_id)
+            .ok_or(TmgError::NotInInventory)?;
+        if self.listed.contains_key(&attribute_id) {
+            return Err(TmgError::AttributeListed);
+        }
+
+        let reply = msg::send_for_reply_as::<_, StoreEvent>(
+            store_id,
+            StoreAction::GetAttributeMetadata { attribute_id },
+            0,
+            0,
+        )
+        .expect("Error in sending a message `StoreAction::GetAttributeMetadata`")
+        .await;
+
+        let (slot, effects) = match reply {
+            Ok(StoreEvent::AttributeMetadata { metadata, .. }) => (
+                metadata.slot.ok_or(TmgError::NotEquippable)?,
+                metadata.effects.unwrap_or_default(),
+            ),
+            _ => return Err(TmgError::StoreRejected),
+        };
+
+        // The decay so far is computed with the rates before the change.
+        self.update_stats();
+        self.equipment.insert(slot, attribute_id);
+        self.effects.insert(slot, effects);
+        Ok(slot)
+    }
+
+    /// Returns an attribute to the store it was bought from for a refund.
+    pub async fn return_attribute(&mut self, attribute_id: AttributeId) -> Result<(), TmgError> {
+        self.ensure_owner()?;
+        let store_id = *self
+            .inventory
+            .get(&attribute_id)
+            .ok_or(TmgError::NotInInventory)?;
+
+        let reply = msg::send_for_reply_as::<_, StoreEvent>(
+            store_id,
+            StoreAction::ReturnAttribute { attribute_id },
+            0,
+            0,
+        )
+        .expect("Error in sending a message `StoreAction::ReturnAttribute`")
+        .await;
+
+        match reply {
+            Ok(StoreEvent::Refunded { success: true, .. }) => {
+                self.drop_attribute(attribute_id);
+                Ok(())
+            }
+            Ok(StoreEvent::ReturnWindowClosed { .. }) => Err(TmgError::ReturnWindowClosed),
+            _ => Err(TmgError::StoreRejected),
+        }
+    }
+
+    /// Gives an owned attribute to another tamagotchi, taking it off if it's worn.
+    pub async fn transfer_attribute(
+        &mut self,
+        to: ActorId,
+        attribute_id: AttributeId,
+    ) -> Result<(), TmgError> {
+        self.ensure_owner()?;
+        let store_id = *self
+            .inventory
+            .get(&attribute_id)
+            .ok_or(TmgError::NotInInventory)?;
+
+        let reply = msg::send_for_reply_as::<_, StoreEvent>(
+            store_id,
+            StoreAction::TransferAttribute { to, attribute_id },
+            0,
+            0,
+        )
+        .expect("Error in sending a message `StoreAction::TransferAttribute`")
+        .await;
+
+        match reply {
+            Ok(StoreEvent::AttributeTransferred { .. }) => {
+                self.drop_attribute(attribute_id);
+                Ok(())
+            }
+            _ => Err(TmgError::StoreRejected),
+        }
+    }
+
+    /// Makes the inventory match the attributes the store lists for this tamagotchi.
+    ///
+    /// Picks up gifts and transfers from other tamagotchis and drops refunded attributes.
+    pub async fn sync_inventory(&mut self, store_id: &ActorId) -> Result<(), TmgError> {
+        self.ensure_owner()?;
+
+        let reply = msg::send_for_reply_as::<_, StoreEvent>(
+            *store_id,
+            StoreAction::GetAttributes {
+                tamagotchi_id: exec::program_id(),
+            },
+            0,
+            0,
+        )
+        .expect("Error in sending a message `StoreAction::GetAttributes`")
+        .await;
+
+        let Ok(StoreEvent::Attributes { attributes }) = reply else {
+            return Err(TmgError::StoreRejected);
+        };
+        let dropped: Vec<AttributeId> = self
+            .inventory
+            .iter()
+            .filter(|(attribute_id, id)| *id == store_id && !attributes.contains(*attribute_id))
+            .map(|(attribute_id, _)| *attribute_id)
+            .collect();
+        for attribute_id in dropped {
+            self.drop_attribute(attribute_id);
+        }
+        for attribute_id in attributes {
+            self.inventory.insert(attribute_id, *store_id);
+        }
+        Ok(())
+    }
+
+    fn drop_attribute(&mut self, attribute_id: AttributeId) {
+        self.inventory.remove(&attribute_id);
+        self.listed.remove(&attribute_id);
+        if let Some(slot) = self
+            .equipment
+            .iter()
+            .find_map(|(slot, id)| (*id == attribute_id).then_some(*slot))
+        {
+            self.update_stats();
+            self.equipment.remove(&slot);
+            self.effects.remove(&slot);
+        }
+    }
+
+    pub fn unequip(&mut self, slot: Slot) -> Result<AttributeId, TmgError> {
+        self.ensure_owner()?;
+        let attribute_id = self.equipment.remove(&slot).ok_or(TmgError::SlotEmpty)?;
+
+        self.update_stats();
+        self.effects.remove(&slot);
+        Ok(attribute_id)
+    }
+}
@@ -0,0 +1,344 @@
+use crate::{TamagotchiState, TmgError};
+use gstd::{exec, prelude::*};
+use store_io::AttrEffects;
+
+/// Decay and fill parameters of a tamagotchi.
+#[derive(Encode, Decode, TypeInfo, Debug, Clone, Copy, PartialEq, Eq)]
+#[codec(crate = gstd::codec)]
+#[scale_info(crate = gstd::scale_info)]
+pub struct StatConfig {
+    pub hunger_per_block: u32,
+    pub hunger_threshold: u32,
+    pub fill_per_feed: u32,
+    pub boredom_per_block: u32,
+    pub boredom_threshold: u32,
+    pub fill_per_entertainment: u32,
+    pub energy_per_block: u32,
+    pub energy_threshold: u32,
+    pub fill_per_sleep: u32,
+    pub max_stat_value: u32,
+    /// How many blocks any stat may stay at zero before the tamagotchi dies.
+    pub blocks_to_death: u32,
+    /// Price of a revival in the fungible token, free if zero.
+    pub revival_price: u128,
+}
+
+impl StatConfig {
+    pub fn is_valid(&self) -> bool {
+        self.max_stat_value > 0
+    }
+}
+
+/// Stat values at the current block.
+#[derive(Encode, Decode, TypeInfo, Debug, Clone, Copy, PartialEq, Eq)]
+#[codec(crate = gstd::codec)]
+#[scale_info(crate = gstd::scale_info)]
+pub struct StatValues {
+    pub fed: u32,
+    pub entertained: u32,
+    pub slept: u32,
+}
+
+impl Default for StatConfig {
+    fn default() -> Self {
+        Self {
+            hunger_per_block: 1,
+            hunger_threshold: 2000,
+            fill_per_feed: 1000,
+            boredom_per_block: 2,
+            boredom_threshold: 2000,
+            fill_per_entertainment: 1000,
+            energy_per_block: 2,
+            energy_threshold: 2000,
+            fill_per_sleep: 1000,
+            max_stat_value: 10000,
+            blocks_to_death: 1000,
+            revival_price: 0,
+        }
+    }
+}
+
+#[derive(Encode, Decode, TypeInfo, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[codec(crate = gstd::codec)]
+#[scale_info(crate = gstd::scale_info)]
+pub enum Lifecycle {
+    #[default]
+    Alive,
+    Dead {
+        died_at: u32,
+    },
+}
+
+impl TamagotchiState {
+    /// Returns the config with the effects of the equipped attributes applied.
+    pub fn effective_config(&self) -> StatConfig {
+        let total =
+            |effect: fn(&AttrEffects) -> i32| self.effects.values().map(effect).sum::<i32>();
+        let config = self.config;
+
+        StatConfig {
+            hunger_per_block: scale_rate(
+                config.hunger_per_block,
+                total(|e| e.hunger_decay_percent),
+            ),
+            boredom_per_block: scale_rate(
+                config.boredom_per_block,
+                total(|e| e.boredom_decay_percent),
+            ),
+            energy_per_block: scale_rate(
+                config.energy_per_block,
+                total(|e| e.energy_decay_percent),
+            ),
+            fill_per_feed: config
+                .fill_per_feed
+                .saturating_add_signed(total(|e| e.fill_per_feed)),
+            fill_per_entertainment: config
+                .fill_per_entertainment
+                .saturating_add_signed(total(|e| e.fill_per_entertainment)),
+            fill_per_sleep: config
+                .fill_per_sleep
+                .saturating_add_signed(total(|e| e.fill_per_sleep)),
+            ..config
+        }
+    }
+
+    pub fn age(&self) -> u64 {
+        exec::block_timestamp() - self.date_of_birth
+    }
+
+    pub fn current_fed(&self) -> u32 {
+        let config = self.effective_config();
+        current_stat(self.fed, self.fed_block, config.hunger_per_block)
+    }
+
+    pub fn current_entertained(&self) -> u32 {
+        let config = self.effective_config();
+        current_stat(
+            self.entertained,
+            self.entertained_block,
+            config.boredom_per_block,
+        )
+    }
+
+    pub fn current_slept(&self) -> u32 {
+        let config = self.effective_config();
+        current_stat(self.slept, self.slept_block, config.energy_per_block)
+    }
+
+    /// Applies the decay to all stats and moves their blocks to the current one.
+    pub fn update_stats(&mut self) {
+        let config = self.effective_config();
+        sync_stat(&mut self.fed, &mut self.fed_block, config.hunger_per_block);
+        sync_stat(
+            &mut self.entertained,
+            &mut self.entertained_block,
+            config.boredom_per_block,
+        );
+        sync_stat(
+            &mut self.slept,
+            &mut self.slept_block,
+            config.energy_per_block,
+        );
+    }
+
+    pub fn feed(&mut self) -> u32 {
+        let config = self.effective_config();
+        fill_stat(
+            &mut self.fed,
+            &mut self.fed_block,
+            config.hunger_per_block,
+            config.fill_per_feed,
+            config.max_stat_value,
+        )
+    }
+
+    pub fn entertain(&mut self) -> u32 {
+        let config = self.effective_config();
+        fill_stat(
+            &mut self.entertained,
+            &mut self.entertained_block,
+            config.boredom_per_block,
+            config.fill_per_entertainment,
+            config.max_stat_value,
+        )
+    }
+
+    pub fn sleep(&mut self) -> u32 {
+        let config = self.effective_config();
+        fill_stat(
+            &mut self.slept,
+            &mut self.slept_block,
+            config.energy_per_block,
+            config.fill_per_sleep,
+            config.max_stat_value,
+        )
+    }
+
+    pub fn is_hungry(&self) -> bool {
+        self.fed <= self.config.hunger_threshold
+    }
+
+    pub fn is_bored(&self) -> bool {
+        self.entertained <= self.config.boredom_threshold
+    }
+
+    pub fn is_tired(&self) -> bool {
+        self.slept <= self.config.energy_threshold
+    }
+
+    pub fn has_needs(&self) -> bool {
+        self.is_hungry() || self.is_bored() || self.is_tired()
+    }
+
+    pub fn stat_values(&self) -> StatValues {
+        StatValues {
+            fed: self.current_fed(),
+            entertained: self.current_entertained(),
+            slept: self.current_slept(),
+        }
+    }
+
+    /// Applies the decay accumulated under the old config before switching to the new one.
+    pub fn update_config(&mut self, config: StatConfig) -> Result<(), TmgError> {
+        self.ensure_owner()?;
+        if !config.is_valid() {
+            return Err(TmgError::InvalidConfig);
+        }
+
+        self.update_stats();
+        self.config = config;
+        self.fed = self.fed.min(config.max_stat_value);
+        self.entertained = self.entertained.min(config.max_stat_value);
+        self.slept = self.slept.min(config.max_stat_value);
+        Ok(())
+    }
+
+    pub fn is_dead(&self) -> bool {
+        matches!(self.lifecycle, Lifecycle::Dead { .. })
+    }
+
+    pub fn ensure_alive(&self) -> Result<(), TmgError> {
+        if self.is_dead() {
+            return Err(TmgError::Dead);
+        }
+        Ok(())
+    }
+
+    /// Returns the block at which the tamagotchi dies (or has died) of an exhausted stat.
+    pub fn death_block(&self) -> Option<u32> {
+        let config = self.effective_config();
+        [
+            (self.fed, self.fed_block, config.hunger_per_block),
+            (
+                self.entertained,
+                self.entertained_block,
+                config.boredom_per_block,
+            ),
+            (self.slept, self.slept_block, config.energy_per_block),
+        ]
+        .into_iter()
+        .filter_map(|(stat, stat_block, stat_wasted_per_block)| {
+            exhausted_at(stat, stat_block, stat_wasted_per_block)
+        })
+        .min()
+        .map(|exhausted_block| exhausted_block.saturating_add(config.blocks_to_death))
+        .filter(|death_block| *death_block <= exec::block_height())
+    }
+
+    /// Marks the tamagotchi as dead if a stat has been exhausted for too long.
+    ///
+    /// Returns `true` only when the death happens during this call.
+    pub fn update_lifecycle(&mut self) -> bool {
+        if self.is_dead() {
+            return false;
+        }
+        match self.death_block() {
+            Some(died_at) => {
+                self.lifecycle = Lifecycle::Dead { died_at };
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub async fn revive(&mut self) -> Result<(), TmgError> {
+        self.ensure_owner()?;
+        if !self.is_dead() {
+            return Err(TmgError::NotDead);
+        }
+
+        let owner = self.owner;
+        let revival_price = self.config.revival_price;
+        if revival_price > 0 {
+            self.transfer_tokens(&owner, &exec::program_id(), revival_price)
+                .await?;
+        }
+
+        self.restore_stats();
+        self.lifecycle = Lifecycle::Alive;
+        Ok(())
+    }
+
+    pub(crate) fn restore_stats(&mut self) {
+        let current_block = exec::block_height();
+        let max_stat_value = self.config.max_stat_value;
+
+        self.fed = max_stat_value;
+        self.fed_block = current_block;
+        self.entertained = max_stat_value;
+        self.entertained_block = current_block;
+        self.slept = max_stat_value;
+        self.slept_block = current_block;
+    }
+}
+
+/// Returns the value of a stat stored at `stat_block` after the decay up to the current block.
+pub fn current_stat(stat: u32, stat_block: u32, stat_wasted_per_block: u32) -> u32 {
+    let blocks_passed = exec::block_height().saturating_sub(stat_block);
+    let stat_lost = blocks_passed.saturating_mul(stat_wasted_per_block);
+
+    stat.saturating_sub(stat_lost)
+}
+
+/// Changes a decay rate by `percent`, a rate can't go below zero.
+fn scale_rate(rate: u32, percent: i32) -> u32 {
+    let percent = 100i64.saturating_add(percent.into()).max(0);
+    u32::try_from(i64::from(rate) * percent / 100).unwrap_or(u32::MAX)
+}
+
+/// Returns the block at which a stat dropped to zero, if it already did.
+fn exhausted_at(stat: u32, stat_block: u32, stat_wasted_per_block: u32) -> Option<u32> {
+    if stat == 0 {
+        return Some(stat_block);
+    }
+    if stat_wasted_per_block == 0 {
+        return None;
+    }
+
+    let exhausted_block = stat_block.saturating_add(stat.div_ceil(stat_wasted_per_block));
+    (exhausted_block <= exec::block_height()).then_some(exhausted_block)
+}
+
+fn sync_stat(stat: &mut u32, stat_block: &mut u32, stat_wasted_per_block: u32) {
+    let exhausted_block = exhausted_at(*stat, *stat_block, stat_wasted_per_block);
+
+    *stat = current_stat(*stat, *stat_block, stat_wasted_per_block);
+    *stat_block = exhausted_block.unwrap_or_else(exec::block_height);
+}
+
+fn fill_stat(
+    stat: &mut u32,
+    stat_block: &mut u32,
+    stat_wasted_per_block: u32,
+    fill_per_action: u32,
+    max_stat_value: u32,
+) -> u32 {
+    let actual_value = current_stat(*stat, *stat_block, stat_wasted_per_block);
+
+    *stat = actual_value
+        .saturating_add(fill_per_action)
+        .min(max_stat_value);
+    *stat_block = exec::block_height();
+
+    *stat
+}
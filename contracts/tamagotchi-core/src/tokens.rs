@@ -0,0 +1,99 @@
+use crate::{TamagotchiState, TmgError};
+use gstd::{msg, ActorId};
+use sharded_fungible_token_io::{FTokenAction, FTokenEvent, LogicAction};
+use store_io::TransactionId;
+
+impl TamagotchiState {
+    /// Approves `amount` tokens to `account` through the fungible token contract.
+    ///
+    /// The approval is recorded as pending before the message is sent, so a retry
+    /// with the same account and amount reuses its transaction id and the token
+    /// contract doesn't apply it twice. A different request replaces the pending one.
+    /// Returns `false` if the approval failed.
+    pub async fn approve_tokens(
+        &mut self,
+        account: &ActorId,
+        amount: u128,
+    ) -> Result<bool, TmgError> {
+        self.ensure_owner()?;
+        let ft_contract_id = self.ft_contract_id.ok_or(TmgError::FtContractNotSet)?;
+
+        let transaction_id = match self.approve_transaction {
+            Some((transaction_id, pending_account, pending_amount))
+                if pending_account == *account && pending_amount == amount =>
+            {
+                transaction_id
+            }
+            _ => {
+                let transaction_id = self.next_transaction_id();
+                self.approve_transaction = Some((transaction_id, *account, amount));
+                transaction_id
+            }
+        };
+
+        let reply = msg::send_for_reply_as::<_, FTokenEvent>(
+            ft_contract_id,
+            FTokenAction::Message {
+                transaction_id,
+                payload: LogicAction::Approve {
+                    approved_account: *account,
+                    amount,
+                },
+            },
+            0,
+            0,
+        )
+        .expect("Error in sending a message `FTokenAction::Message`")
+        .await;
+
+        match reply {
+            Ok(FTokenEvent::Ok) => {
+                self.approve_transaction = None;
+                Ok(true)
+            }
+            // The token contract has processed the transaction, a retry must use a new one.
+            Ok(_) => {
+                self.approve_transaction = None;
+                Ok(false)
+            }
+            Err(_) => Ok(false),
+        }
+    }
+
+    pub(crate) async fn transfer_tokens(
+        &mut self,
+        sender: &ActorId,
+        recipient: &ActorId,
+        amount: u128,
+    ) -> Result<(), TmgError> {
+        let ft_contract_id = self.ft_contract_id.ok_or(TmgError::FtContractNotSet)?;
+        let transaction_id = self.next_transaction_id();
+
+        let reply = msg::send_for_reply_as::<_, FTokenEvent>(
+            ft_contract_id,
+            FTokenAction::Message {
+                transaction_id,
+                payload: LogicAction::Transfer {
+                    sender: *sender,
+                    recipient: *recipient,
+                    amount,
+                },
+            },
+            0,
+            0,
+        )
+        .expect("Error in sending a message `FTokenAction::Message`")
+        .await;
+
+        match reply {
+            Ok(FTokenEvent::Ok) => Ok(()),
+            _ => Err(TmgError::FtTransferFailed),
+        }
+    }
+
+    pub(crate) fn next_transaction_id(&mut self) -> TransactionId {
+        let transaction_id = self.transaction_id;
+        self.transaction_id = self.transaction_id.wrapping_add(1);
+        transaction_id
+    }
+}
@@ -0,0 +1,125 @@
+mod common;
+
+use common::{init_tamagotchi, read_state, OWNER};
+use gtest::{Log, Program, RunResult, System};
+use sharded_fungible_token_io::{FTokenAction, FTokenEvent, LogicAction};
+use tamagotchi_utils_io::{TmgAction, TmgEvent};
+
+const STORE: u64 = 3;
+// A plain account never replies, so every approval stays pending until a test answers it.
+const FT_CONTRACT: u64 = 4;
+
+fn init(sys: &System) -> Program<'_> {
+    let tamagotchi = init_tamagotchi(sys, None);
+    let res = tamagotchi.send(OWNER, TmgAction::SetFTokenContract(FT_CONTRACT.into()));
+    assert!(!res.main_failed());
+    tamagotchi
+}
+
+fn approve_tokens(tamagotchi: &Program<'_>, amount: u128, transaction_id: u64) {
+    let res = tamagotchi.send(
+        OWNER,
+        TmgAction::ApproveTokens {
+            account: STORE.into(),
+            amount,
+        },
+    );
+    assert!(!res.main_failed());
+    assert!(res.contains(
+        &Log::builder()
+            .dest(FT_CONTRACT)
+            .payload(FTokenAction::Message {
+                transaction_id,
+                payload: LogicAction::Approve {
+                    approved_account: STORE.into(),
+                    amount,
+                },
+            })
+    ));
+}
+
+/// Answers a pending approval as the token contract.
+fn reply_as_ft(sys: &System, amount: u128, transaction_id: u64, event: FTokenEvent) -> RunResult {
+    sys.get_mailbox(FT_CONTRACT)
+        .reply(
+            Log::builder()
+                .dest(FT_CONTRACT)
+                .payload(FTokenAction::Message {
+                    transaction_id,
+                    payload: LogicAction::Approve {
+                        approved_account: STORE.into(),
+                        amount,
+                    },
+                }),
+            event,
+            0,
+        )
+        .expect("Unable to reply as the token contract")
+}
+
+#[test]
+fn retry_reuses_pending_approval() {
+    let sys = System::new();
+    let tamagotchi = init(&sys);
+
+    approve_tokens(&tamagotchi, 100, 0);
+    let state = read_state(&tamagotchi);
+    assert_eq!(state.approve_transaction, Some((0, STORE.into(), 100)));
+    assert_eq!(state.transaction_id, 1);
+
+    approve_tokens(&tamagotchi, 100, 0);
+    let state = read_state(&tamagotchi);
+    assert_eq!(state.approve_transaction, Some((0, STORE.into(), 100)));
+    assert_eq!(state.transaction_id, 1);
+}
+
+#[test]
+fn new_request_replaces_pending_approval() {
+    let sys = System::new();
+    let tamagotchi = init(&sys);
+
+    approve_tokens(&tamagotchi, 100, 0);
+    approve_tokens(&tamagotchi, 250, 1);
+
+    let state = read_state(&tamagotchi);
+    assert_eq!(state.approve_transaction, Some((1, STORE.into(), 250)));
+    assert_eq!(state.transaction_id, 2);
+}
+
+#[test]
+fn rejected_approval_is_reported() {
+    let sys = System::new();
+    let tamagotchi = init(&sys);
+
+    approve_tokens(&tamagotchi, 100, 0);
+    let res = reply_as_ft(&sys, 100, 0, FTokenEvent::Err);
+    assert!(res.contains(&Log::builder().dest(OWNER).payload(TmgEvent::ApprovalError)));
+
+    // the token contract has processed the transaction, a retry gets a new one
+    assert_eq!(read_state(&tamagotchi).approve_transaction, None);
+    approve_tokens(&tamagotchi, 100, 1);
+}
+
+#[test]
+fn completed_approval_is_cleared() {
+    let sys = System::new();
+    let tamagotchi = init(&sys);
+
+    approve_tokens(&tamagotchi, 100, 0);
+    let res = reply_as_ft(&sys, 100, 0, FTokenEvent::Ok);
+    assert!(res.contains(
+        &Log::builder()
+            .dest(OWNER)
+            .payload(TmgEvent::TokensApproved {
+                account: STORE.into(),
+                amount: 100,
+            })
+    ));
+    assert_eq!(read_state(&tamagotchi).approve_transaction, None);
+
+    // the same approval again is a new transaction
+    approve_tokens(&tamagotchi, 100, 1);
+    let state = read_state(&tamagotchi);
+    assert_eq!(state.approve_transaction, Some((1, STORE.into(), 100)));
+    assert_eq!(state.transaction_id, 2);
+}
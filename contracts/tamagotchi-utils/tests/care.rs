@@ -0,0 +1,262 @@
+mod common;
+
+use common::{
+    approve_store, assert_balance, create_attribute, init_ft, init_store, init_tamagotchi,
+    read_state, read_store, send_ft, BALANCE, OWNER, STORE_ID, STRANGER, TAMAGOTCHI_ID,
+};
+use gtest::{Log, Program, System};
+use sharded_fungible_token_io::LogicAction;
+use tamagotchi_utils_io::{
+    Care, CarePolicy, StatConfig, StatValues, TmgAction, TmgError, TmgEvent,
+};
+
+const FOOD: u32 = 1;
+const FOOD_PRICE: u128 = 100;
+
+const FILL_PER_FEED: u32 = 1000;
+const FILL_PER_ENTERTAINMENT: u32 = 1000;
+const MAX_STAT_VALUE: u32 = 10000;
+
+/// Blocks between two `CheckState` messages of the loop.
+const DELAY: u32 = 20;
+const RESERVATION_AMOUNT: u64 = 10_000_000_000;
+
+/// The owner lets the tamagotchi spend its tokens at a store selling food.
+fn init_shop(sys: &System) -> (Program<'_>, Program<'_>) {
+    let ft = init_ft(sys);
+    send_ft(
+        &ft,
+        OWNER,
+        0,
+        LogicAction::Mint {
+            recipient: OWNER.into(),
+            amount: BALANCE,
+        },
+    );
+    send_ft(
+        &ft,
+        OWNER,
+        1,
+        LogicAction::Approve {
+            approved_account: TAMAGOTCHI_ID.into(),
+            amount: BALANCE,
+        },
+    );
+
+    let store = init_store(sys);
+    create_attribute(&store, FOOD, FOOD_PRICE, None, None);
+    (ft, store)
+}
+
+/// The tamagotchi gets hungry at every check of its loop and never bored or tired.
+fn hungry_config() -> Option<StatConfig> {
+    Some(StatConfig {
+        hunger_per_block: 450,
+        boredom_per_block: 0,
+        energy_per_block: 0,
+        ..Default::default()
+    })
+}
+
+fn policy(food: Option<u32>, spending_cap: u128) -> CarePolicy {
+    CarePolicy {
+        store_id: STORE_ID.into(),
+        food,
+        toy: None,
+        bed: None,
+        spending_cap,
+        period: 1000,
+    }
+}
+
+fn set_policy(tamagotchi: &Program<'_>, from: u64, policy: CarePolicy, expected: TmgEvent) {
+    let res = tamagotchi.send(from, TmgAction::SetCarePolicy(Some(policy)));
+    assert!(res.contains(&Log::builder().dest(from).payload(expected)));
+}
+
+fn start_checks(tamagotchi: &Program<'_>) {
+    for _ in 0..3 {
+        let res = tamagotchi.send(
+            OWNER,
+            TmgAction::ReserveGas {
+                reservation_amount: RESERVATION_AMOUNT,
+                duration: 1000,
+            },
+        );
+        assert!(res.contains(&Log::builder().dest(OWNER).payload(TmgEvent::GasReserved)));
+    }
+}
+
+fn hungry(fed: u32) -> TmgEvent {
+    TmgEvent::Needs {
+        hungry: true,
+        bored: false,
+        tired: false,
+        values: StatValues {
+            fed,
+            entertained: MAX_STAT_VALUE,
+            slept: MAX_STAT_VALUE,
+        },
+    }
+}
+
+fn owner_got(sys: &System, event: TmgEvent) -> bool {
+    sys.get_mailbox(OWNER)
+        .contains(&Log::builder().dest(OWNER).payload(event))
+}
+
+#[test]
+fn only_owner_sets_valid_policy() {
+    let sys = System::new();
+    let tamagotchi = init_tamagotchi(&sys, hungry_config());
+
+    set_policy(
+        &tamagotchi,
+        STRANGER,
+        policy(None, 0),
+        TmgEvent::Error(TmgError::NotOwner),
+    );
+    set_policy(
+        &tamagotchi,
+        OWNER,
+        CarePolicy {
+            period: 0,
+            ..policy(None, 0)
+        },
+        TmgEvent::Error(TmgError::InvalidCarePolicy),
+    );
+    assert_eq!(read_state(&tamagotchi).care_policy, None);
+}
+
+#[test]
+fn tamagotchi_feeds_itself() {
+    let sys = System::new();
+    let tamagotchi = init_tamagotchi(&sys, hungry_config());
+    set_policy(
+        &tamagotchi,
+        OWNER,
+        policy(None, 0),
+        TmgEvent::CarePolicySet(Some(policy(None, 0))),
+    );
+    start_checks(&tamagotchi);
+
+    sys.spend_blocks(DELAY);
+    assert!(owner_got(&sys, TmgEvent::CareGiven(Care::Feed)));
+    // one portion of food isn't enough to stop being hungry
+    assert!(owner_got(&sys, hungry(FILL_PER_FEED)));
+}
+
+#[test]
+fn tamagotchi_cares_for_every_need() {
+    let sys = System::new();
+    let tamagotchi = init_tamagotchi(&sys, hungry_config());
+    let config = StatConfig {
+        hunger_per_block: 450,
+        boredom_per_block: 450,
+        energy_per_block: 0,
+        ..Default::default()
+    };
+    let res = tamagotchi.send(OWNER, TmgAction::UpdateConfig(config));
+    assert!(res.contains(
+        &Log::builder()
+            .dest(OWNER)
+            .payload(TmgEvent::ConfigUpdated(config))
+    ));
+    set_policy(
+        &tamagotchi,
+        OWNER,
+        policy(None, 0),
+        TmgEvent::CarePolicySet(Some(policy(None, 0))),
+    );
+    start_checks(&tamagotchi);
+
+    sys.spend_blocks(DELAY);
+    assert!(owner_got(&sys, TmgEvent::CareGiven(Care::Feed)));
+    assert!(owner_got(&sys, TmgEvent::CareGiven(Care::Entertain)));
+    assert!(owner_got(
+        &sys,
+        TmgEvent::Needs {
+            hungry: true,
+            bored: true,
+            tired: false,
+            values: StatValues {
+                fed: FILL_PER_FEED,
+                entertained: FILL_PER_ENTERTAINMENT,
+                slept: MAX_STAT_VALUE,
+            },
+        }
+    ));
+}
+
+#[test]
+fn consumables_are_bought_within_cap() {
+    let sys = System::new();
+    let (ft, store) = init_shop(&sys);
+    let tamagotchi = init_tamagotchi(&sys, hungry_config());
+    approve_store(&tamagotchi, BALANCE);
+
+    // enough for one portion of food per period
+    let policy = policy(Some(FOOD), FOOD_PRICE * 3 / 2);
+    set_policy(
+        &tamagotchi,
+        OWNER,
+        policy,
+        TmgEvent::CarePolicySet(Some(policy)),
+    );
+    start_checks(&tamagotchi);
+
+    sys.spend_blocks(DELAY);
+    assert!(owner_got(&sys, TmgEvent::CareGiven(Care::Feed)));
+    assert_eq!(read_state(&tamagotchi).care_spending.spent, FOOD_PRICE);
+
+    sys.spend_blocks(DELAY);
+    assert!(owner_got(&sys, hungry(0)));
+    assert_eq!(read_state(&tamagotchi).care_spending.spent, FOOD_PRICE);
+
+    let state = read_store(&store);
+    assert_eq!(state.revenue, FOOD_PRICE);
+    // the owner paid for the food
+    assert_balance(&ft, OWNER, BALANCE - FOOD_PRICE);
+    assert_balance(&ft, TAMAGOTCHI_ID, 0);
+}
+
+#[test]
+fn spending_starts_again_next_period() {
+    let sys = System::new();
+    let (ft, store) = init_shop(&sys);
+    let tamagotchi = init_tamagotchi(&sys, hungry_config());
+    approve_store(&tamagotchi, BALANCE);
+
+    // the third check of the loop is in the next period
+    let policy = CarePolicy {
+        period: DELAY * 5 / 2,
+        ..policy(Some(FOOD), FOOD_PRICE * 3 / 2)
+    };
+    set_policy(
+        &tamagotchi,
+        OWNER,
+        policy,
+        TmgEvent::CarePolicySet(Some(policy)),
+    );
+    start_checks(&tamagotchi);
+    let period_start = read_state(&tamagotchi).care_spending.period_start;
+
+    sys.spend_blocks(DELAY);
+    assert!(owner_got(&sys, TmgEvent::CareGiven(Care::Feed)));
+
+    // the cap is hit
+    sys.spend_blocks(DELAY);
+    assert!(owner_got(&sys, hungry(0)));
+    let state = read_state(&tamagotchi);
+    assert_eq!(state.care_spending.period_start, period_start);
+    assert_eq!(state.care_spending.spent, FOOD_PRICE);
+
+    sys.spend_blocks(DELAY);
+    let state = read_state(&tamagotchi);
+    assert!(state.care_spending.period_start > period_start);
+    assert_eq!(state.care_spending.spent, FOOD_PRICE);
+
+    let state = read_store(&store);
+    assert_eq!(state.revenue, FOOD_PRICE * 2);
+    assert_balance(&ft, OWNER, BALANCE - FOOD_PRICE * 2);
+}
@@ -0,0 +1,144 @@
+#![allow(dead_code)]
+
+use gtest::{Log, Program, System};
+use sharded_fungible_token_io::{FTokenAction, FTokenEvent, InitFToken, LogicAction};
+use store_io::{AttrEffects, AttrMetadata, AttributeStore, Slot, StoreAction, StoreEvent};
+use tamagotchi_utils_io::{StatConfig, Tamagotchi, TmgAction, TmgEvent, TmgInit};
+
+pub const FT_MAIN_WASM: &str =
+    "../target/wasm32-unknown-unknown/debug/sharded_fungible_token.opt.wasm";
+pub const FT_LOGIC_WASM: &str =
+    "../target/wasm32-unknown-unknown/debug/sharded_fungible_token_logic.opt.wasm";
+pub const FT_STORAGE_WASM: &str =
+    "../target/wasm32-unknown-unknown/debug/sharded_fungible_token_storage.opt.wasm";
+pub const STORE_WASM: &str = "../target/wasm32-unknown-unknown/debug/store.opt.wasm";
+pub const MARKETPLACE_WASM: &str = "../target/wasm32-unknown-unknown/debug/marketplace.opt.wasm";
+
+pub const OWNER: u64 = 2;
+pub const ADMIN: u64 = 3;
+pub const STRANGER: u64 = 4;
+pub const FRIEND: u64 = 5;
+pub const TAMAGOTCHI_ID: u64 = 10;
+pub const FT_ID: u64 = 11;
+pub const STORE_ID: u64 = 12;
+pub const MARKETPLACE_ID: u64 = 13;
+
+pub const BALANCE: u128 = 1000;
+
+pub fn init_tamagotchi(sys: &System, config: Option<StatConfig>) -> Program<'_> {
+    sys.init_logger();
+    let tamagotchi = Program::current_with_id(sys, TAMAGOTCHI_ID);
+    let res = tamagotchi.send(
+        OWNER,
+        TmgInit {
+            owner: OWNER.into(),
+            name: String::from("Luchex"),
+            config,
+        },
+    );
+    assert!(!res.main_failed());
+    tamagotchi
+}
+
+pub fn read_state(tamagotchi: &Program<'_>) -> Tamagotchi {
+    tamagotchi
+        .read_state(0)
+        .expect("Unable to read the tamagotchi state")
+}
+
+/// Deploys the token contract without any balances.
+pub fn init_ft(sys: &System) -> Program<'_> {
+    let storage_code_id = sys.submit_code(FT_STORAGE_WASM);
+    let logic_code_id = sys.submit_code(FT_LOGIC_WASM);
+    let ft = Program::from_file_with_id(sys, FT_ID, FT_MAIN_WASM);
+    let res = ft.send(
+        ADMIN,
+        InitFToken {
+            storage_code_hash: storage_code_id.into_bytes().into(),
+            ft_logic_code_hash: logic_code_id.into_bytes().into(),
+        },
+    );
+    assert!(!res.main_failed());
+    ft
+}
+
+pub fn send_ft(ft: &Program<'_>, from: u64, transaction_id: u64, payload: LogicAction) {
+    let res = ft.send(
+        from,
+        FTokenAction::Message {
+            transaction_id,
+            payload,
+        },
+    );
+    assert!(res.contains(&Log::builder().dest(from).payload(FTokenEvent::Ok)));
+}
+
+pub fn assert_balance(ft: &Program<'_>, account: u64, balance: u128) {
+    let res = ft.send(ADMIN, FTokenAction::GetBalance(account.into()));
+    assert!(res.contains(
+        &Log::builder()
+            .dest(ADMIN)
+            .payload(FTokenEvent::Balance(balance))
+    ));
+}
+
+/// Deploys the store paid with the token contract, without any attributes.
+pub fn init_store(sys: &System) -> Program<'_> {
+    let store = Program::from_file_with_id(sys, STORE_ID, STORE_WASM);
+    let res = store.send(ADMIN, gstd::ActorId::from(FT_ID));
+    assert!(!res.main_failed());
+    store
+}
+
+pub fn create_attribute(
+    store: &Program<'_>,
+    attribute_id: u32,
+    price: u128,
+    slot: Option<Slot>,
+    effects: Option<AttrEffects>,
+) {
+    let res = store.send(
+        ADMIN,
+        StoreAction::CreateAttribute {
+            attribute_id,
+            attr_metadata: AttrMetadata {
+                title: format!("Attribute {attribute_id}"),
+                description: String::new(),
+                media: String::new(),
+                slot,
+                effects,
+            },
+            price,
+        },
+    );
+    assert!(res.contains(
+        &Log::builder()
+            .dest(ADMIN)
+            .payload(StoreEvent::AttributeCreated { attribute_id })
+    ));
+}
+
+pub fn read_store(store: &Program<'_>) -> AttributeStore {
+    store.read_state(0).expect("Unable to read the store state")
+}
+
+/// Sets the token contract and lets the store spend `amount` of the tamagotchi's tokens.
+pub fn approve_store(tamagotchi: &Program<'_>, amount: u128) {
+    let res = tamagotchi.send(OWNER, TmgAction::SetFTokenContract(FT_ID.into()));
+    assert!(!res.main_failed());
+    let res = tamagotchi.send(
+        OWNER,
+        TmgAction::ApproveTokens {
+            account: STORE_ID.into(),
+            amount,
+        },
+    );
+    assert!(res.contains(
+        &Log::builder()
+            .dest(OWNER)
+            .payload(TmgEvent::TokensApproved {
+                account: STORE_ID.into(),
+                amount,
+            })
+    ));
+}
@@ -0,0 +1,210 @@
+mod common;
+
+use common::{
+    init_ft, init_store, init_tamagotchi, read_state, FRIEND, FT_ID, OWNER, STORE_ID, STRANGER,
+};
+use gtest::{Log, Program, System};
+use tamagotchi_utils_io::{Lifecycle, StatConfig, TmgAction, TmgError, TmgEvent};
+
+/// Every stat runs out after 100 blocks and the tamagotchi dies 10 blocks later.
+fn mortal_config() -> StatConfig {
+    StatConfig {
+        hunger_per_block: 100,
+        boredom_per_block: 100,
+        energy_per_block: 100,
+        blocks_to_death: 10,
+        revival_price: 100,
+        ..Default::default()
+    }
+}
+
+fn assert_error(tamagotchi: &Program<'_>, from: u64, action: TmgAction, error: TmgError) {
+    let res = tamagotchi.send(from, action);
+    assert!(!res.main_failed());
+    assert!(res.contains(&Log::builder().dest(from).payload(TmgEvent::Error(error))));
+}
+
+#[test]
+fn owner_only_actions() {
+    let sys = System::new();
+    let tamagotchi = init_tamagotchi(&sys, None);
+
+    assert_error(
+        &tamagotchi,
+        STRANGER,
+        TmgAction::Approve(STRANGER.into()),
+        TmgError::NotOwner,
+    );
+    assert_error(
+        &tamagotchi,
+        STRANGER,
+        TmgAction::RevokeApproval,
+        TmgError::NotOwner,
+    );
+    assert_error(
+        &tamagotchi,
+        STRANGER,
+        TmgAction::SetFTokenContract(FT_ID.into()),
+        TmgError::NotOwner,
+    );
+    assert_error(
+        &tamagotchi,
+        STRANGER,
+        TmgAction::ApproveTokens {
+            account: STRANGER.into(),
+            amount: 100,
+        },
+        TmgError::NotOwner,
+    );
+    assert_error(
+        &tamagotchi,
+        STRANGER,
+        TmgAction::BuyAttribute {
+            store_id: STORE_ID.into(),
+            attribute_id: 1,
+            coupon: None,
+        },
+        TmgError::NotOwner,
+    );
+
+    let state = read_state(&tamagotchi);
+    assert_eq!(state.approved_account, None);
+    assert_eq!(state.ft_contract_id, None);
+}
+
+#[test]
+fn transfer_requires_approval() {
+    let sys = System::new();
+    let tamagotchi = init_tamagotchi(&sys, None);
+
+    assert_error(
+        &tamagotchi,
+        FRIEND,
+        TmgAction::Transfer(FRIEND.into()),
+        TmgError::NotApproved,
+    );
+
+    let res = tamagotchi.send(OWNER, TmgAction::Approve(FRIEND.into()));
+    assert!(res.contains(
+        &Log::builder()
+            .dest(OWNER)
+            .payload(TmgEvent::Approved(FRIEND.into()))
+    ));
+
+    let res = tamagotchi.send(FRIEND, TmgAction::Transfer(FRIEND.into()));
+    assert!(res.contains(
+        &Log::builder()
+            .dest(FRIEND)
+            .payload(TmgEvent::Transferred(FRIEND.into()))
+    ));
+    assert_eq!(read_state(&tamagotchi).owner, FRIEND.into());
+
+    assert_error(
+        &tamagotchi,
+        OWNER,
+        TmgAction::RevokeApproval,
+        TmgError::NotOwner,
+    );
+}
+
+#[test]
+fn tokens_require_ft_contract() {
+    let sys = System::new();
+    let tamagotchi = init_tamagotchi(&sys, None);
+
+    assert_error(
+        &tamagotchi,
+        OWNER,
+        TmgAction::ApproveTokens {
+            account: STORE_ID.into(),
+            amount: 100,
+        },
+        TmgError::FtContractNotSet,
+    );
+}
+
+#[test]
+fn invalid_config_is_rejected() {
+    let sys = System::new();
+    let tamagotchi = init_tamagotchi(&sys, None);
+
+    assert_error(
+        &tamagotchi,
+        OWNER,
+        TmgAction::UpdateConfig(StatConfig {
+            max_stat_value: 0,
+            ..Default::default()
+        }),
+        TmgError::InvalidConfig,
+    );
+    assert_eq!(read_state(&tamagotchi).config, StatConfig::default());
+}
+
+#[test]
+fn failed_payment_is_reported() {
+    let sys = System::new();
+    let tamagotchi = init_tamagotchi(&sys, None);
+    let _ft = init_ft(&sys);
+    for action in [
+        TmgAction::SetFTokenContract(FT_ID.into()),
+        TmgAction::UpdateConfig(mortal_config()),
+    ] {
+        assert!(!tamagotchi.send(OWNER, action).main_failed());
+    }
+    sys.spend_blocks(200);
+
+    // the owner has no tokens to pay for the revival
+    assert_error(
+        &tamagotchi,
+        OWNER,
+        TmgAction::Revive,
+        TmgError::FtTransferFailed,
+    );
+    assert!(matches!(
+        read_state(&tamagotchi).lifecycle,
+        Lifecycle::Dead { .. }
+    ));
+}
+
+#[test]
+fn store_rejection_is_reported() {
+    let sys = System::new();
+    let tamagotchi = init_tamagotchi(&sys, None);
+    let _store = init_store(&sys);
+
+    // the store fails on an attribute it doesn't sell
+    assert_error(
+        &tamagotchi,
+        OWNER,
+        TmgAction::BuyAttribute {
+            store_id: STORE_ID.into(),
+            attribute_id: 1,
+            coupon: None,
+        },
+        TmgError::StoreRejected,
+    );
+    let state = read_state(&tamagotchi);
+    assert!(state.inventory.is_empty());
+    // without a reply the tamagotchi can't tell whether the store started the purchase
+    assert_eq!(state.pending_purchase, Some((STORE_ID.into(), 1, None)));
+}
+
+#[test]
+fn failed_reservation_is_reported() {
+    let sys = System::new();
+    let tamagotchi = init_tamagotchi(&sys, None);
+
+    // a reservation has to last at least one block
+    assert_error(
+        &tamagotchi,
+        OWNER,
+        TmgAction::ReserveGas {
+            reservation_amount: 10_000_000_000,
+            duration: 0,
+        },
+        TmgError::ReservationFailed,
+    );
+    let state = read_state(&tamagotchi);
+    assert!(state.reservations.is_empty());
+    assert!(state.sponsors.is_empty());
+}
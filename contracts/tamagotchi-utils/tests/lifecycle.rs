@@ -0,0 +1,102 @@
+mod common;
+
+use common::{init_tamagotchi, read_state, OWNER, STRANGER};
+use gtest::{Log, System};
+use tamagotchi_utils_io::{Lifecycle, StatConfig, TmgAction, TmgError, TmgEvent};
+
+// Every stat runs out after 100 blocks and the tamagotchi dies 10 blocks later.
+const CONFIG: StatConfig = StatConfig {
+    hunger_per_block: 100,
+    hunger_threshold: 2000,
+    fill_per_feed: 1000,
+    boredom_per_block: 100,
+    boredom_threshold: 2000,
+    fill_per_entertainment: 1000,
+    energy_per_block: 100,
+    energy_threshold: 2000,
+    fill_per_sleep: 1000,
+    max_stat_value: 10000,
+    blocks_to_death: 10,
+    revival_price: 0,
+};
+
+#[test]
+fn exhausted_tamagotchi_dies() {
+    let sys = System::new();
+    let tamagotchi = init_tamagotchi(&sys, Some(CONFIG));
+
+    sys.spend_blocks(105);
+    let state = read_state(&tamagotchi);
+    assert_eq!(state.fed, 0);
+    assert_eq!(state.lifecycle, Lifecycle::Alive);
+
+    sys.spend_blocks(10);
+    let state = read_state(&tamagotchi);
+    assert!(matches!(state.lifecycle, Lifecycle::Dead { .. }));
+
+    let res = tamagotchi.send(STRANGER, TmgAction::Feed);
+    assert!(res.contains(&Log::builder().dest(OWNER).payload(TmgEvent::Died)));
+    assert!(res.contains(
+        &Log::builder()
+            .dest(STRANGER)
+            .payload(TmgEvent::Error(TmgError::Dead))
+    ));
+
+    for action in [TmgAction::Feed, TmgAction::Entertain, TmgAction::Sleep] {
+        let res = tamagotchi.send(OWNER, action);
+        assert!(res.contains(
+            &Log::builder()
+                .dest(OWNER)
+                .payload(TmgEvent::Error(TmgError::Dead))
+        ));
+        assert!(!res.contains(&Log::builder().dest(OWNER).payload(TmgEvent::Died)));
+    }
+}
+
+#[test]
+fn care_in_time_keeps_tamagotchi_alive() {
+    let sys = System::new();
+    let tamagotchi = init_tamagotchi(&sys, Some(CONFIG));
+
+    sys.spend_blocks(105);
+    for action in [TmgAction::Feed, TmgAction::Entertain, TmgAction::Sleep] {
+        assert!(!tamagotchi.send(OWNER, action).main_failed());
+    }
+
+    sys.spend_blocks(5);
+    let state = read_state(&tamagotchi);
+    assert_eq!(state.lifecycle, Lifecycle::Alive);
+    assert!(state.fed > 0);
+}
+
+#[test]
+fn owner_revives_dead_tamagotchi() {
+    let sys = System::new();
+    let tamagotchi = init_tamagotchi(&sys, Some(CONFIG));
+
+    let res = tamagotchi.send(OWNER, TmgAction::Revive);
+    assert!(res.contains(
+        &Log::builder()
+            .dest(OWNER)
+            .payload(TmgEvent::Error(TmgError::NotDead))
+    ));
+
+    sys.spend_blocks(200);
+
+    let res = tamagotchi.send(STRANGER, TmgAction::Revive);
+    assert!(res.contains(
+        &Log::builder()
+            .dest(STRANGER)
+            .payload(TmgEvent::Error(TmgError::NotOwner))
+    ));
+
+    let res = tamagotchi.send(OWNER, TmgAction::Revive);
+    assert!(res.contains(&Log::builder().dest(OWNER).payload(TmgEvent::Revived)));
+
+    let state = read_state(&tamagotchi);
+    assert_eq!(state.lifecycle, Lifecycle::Alive);
+    assert_eq!(state.fed, CONFIG.max_stat_value);
+
+    let res = tamagotchi.send(OWNER, TmgAction::Feed);
+    assert!(res.contains(&Log::builder().dest(OWNER).payload(TmgEvent::Fed)));
+}
@@ -0,0 +1,399 @@
+mod common;
+
+use common::{
+    approve_store, create_attribute, init_ft, init_store, init_tamagotchi, read_state, read_store,
+    send_ft, ADMIN, BALANCE, FT_ID, MARKETPLACE_ID, MARKETPLACE_WASM, OWNER, STORE_ID,
+    TAMAGOTCHI_ID,
+};
+use gtest::{Log, Program, System};
+use marketplace_io::MarketplaceInit;
+use sharded_fungible_token_io::LogicAction;
+use store_io::{AttrEffects, Slot, StoreAction};
+use tamagotchi_utils_io::{TmgAction, TmgError, TmgEvent};
+
+const SWORD: u32 = 1;
+const SWORD_PRICE: u128 = 500;
+const HAT: u32 = 2;
+const HAT_PRICE: u128 = 5000;
+const POTION: u32 = 3;
+const POTION_PRICE: u128 = 100;
+
+const SWORD_EFFECTS: AttrEffects = AttrEffects {
+    hunger_decay_percent: 0,
+    boredom_decay_percent: -50,
+    energy_decay_percent: 0,
+    fill_per_feed: 500,
+    fill_per_entertainment: 0,
+    fill_per_sleep: 0,
+};
+const BOREDOM_PER_BLOCK: u32 = 2;
+const HUNGER_PER_BLOCK: u32 = 1;
+const FILL_PER_FEED: u32 = 1000;
+
+/// The tamagotchi has tokens for the sword and the potion but not for the hat.
+fn init(sys: &System) -> (Program<'_>, Program<'_>) {
+    let ft = init_ft(sys);
+    send_ft(
+        &ft,
+        ADMIN,
+        0,
+        LogicAction::Mint {
+            recipient: TAMAGOTCHI_ID.into(),
+            amount: BALANCE,
+        },
+    );
+
+    let store = init_store(sys);
+    create_attribute(
+        &store,
+        SWORD,
+        SWORD_PRICE,
+        Some(Slot::Accessory),
+        Some(SWORD_EFFECTS),
+    );
+    create_attribute(&store, HAT, HAT_PRICE, Some(Slot::Hat), None);
+    create_attribute(&store, POTION, POTION_PRICE, None, None);
+
+    let tamagotchi = init_tamagotchi(sys, None);
+    approve_store(&tamagotchi, BALANCE);
+    (store, tamagotchi)
+}
+
+fn init_marketplace(sys: &System) -> Program<'_> {
+    let marketplace = Program::from_file_with_id(sys, MARKETPLACE_ID, MARKETPLACE_WASM);
+    let res = marketplace.send(
+        ADMIN,
+        MarketplaceInit {
+            store_id: STORE_ID.into(),
+            ft_contract_id: FT_ID.into(),
+            royalty_percent: 0,
+        },
+    );
+    assert!(!res.main_failed());
+    marketplace
+}
+
+fn buy_attribute(tamagotchi: &Program<'_>, attribute_id: u32, expected: TmgEvent) {
+    let res = tamagotchi.send(
+        OWNER,
+        TmgAction::BuyAttribute {
+            store_id: STORE_ID.into(),
+            attribute_id,
+            coupon: None,
+        },
+    );
+    assert!(res.contains(&Log::builder().dest(OWNER).payload(expected)));
+}
+
+#[test]
+fn buy_attribute_from_store() {
+    let sys = System::new();
+    let (store, tamagotchi) = init(&sys);
+
+    buy_attribute(&tamagotchi, SWORD, TmgEvent::AttributeBought(SWORD));
+
+    let state = read_store(&store);
+    assert!(state.owners[&TAMAGOTCHI_ID.into()].contains(&SWORD));
+    assert!(state.transactions.is_empty());
+
+    let state = read_state(&tamagotchi);
+    assert_eq!(state.pending_purchase, None);
+    assert_eq!(state.inventory.get(&SWORD), Some(&STORE_ID.into()));
+}
+
+#[test]
+fn failed_purchase_is_reported() {
+    let sys = System::new();
+    let (store, tamagotchi) = init(&sys);
+
+    buy_attribute(&tamagotchi, HAT, TmgEvent::ErrorDuringPurchase);
+
+    let state = read_store(&store);
+    assert!(!state.owners.contains_key(&TAMAGOTCHI_ID.into()));
+
+    // The failed purchase is settled, so the next one isn't blocked by it.
+    buy_attribute(&tamagotchi, SWORD, TmgEvent::AttributeBought(SWORD));
+}
+
+#[test]
+fn complete_prev_purchase_requires_pending_one() {
+    let sys = System::new();
+    let (_store, tamagotchi) = init(&sys);
+
+    let res = tamagotchi.send(OWNER, TmgAction::CompletePrevPurchase);
+    assert!(res.contains(
+        &Log::builder()
+            .dest(OWNER)
+            .payload(TmgEvent::Error(TmgError::NoPendingPurchase))
+    ));
+}
+
+#[test]
+fn equip_and_unequip_attributes() {
+    let sys = System::new();
+    let (_store, tamagotchi) = init(&sys);
+
+    buy_attribute(&tamagotchi, SWORD, TmgEvent::AttributeBought(SWORD));
+    buy_attribute(&tamagotchi, POTION, TmgEvent::AttributeBought(POTION));
+
+    let res = tamagotchi.send(OWNER, TmgAction::Equip(SWORD));
+    assert!(
+        res.contains(&Log::builder().dest(OWNER).payload(TmgEvent::Equipped {
+            attribute_id: SWORD,
+            slot: Slot::Accessory,
+        }))
+    );
+    let state = read_state(&tamagotchi);
+    assert_eq!(state.equipment.get(&Slot::Accessory), Some(&SWORD));
+
+    for (action, error) in [
+        (TmgAction::Equip(HAT), TmgError::NotInInventory),
+        (TmgAction::Equip(POTION), TmgError::NotEquippable),
+        (TmgAction::Unequip(Slot::Hat), TmgError::SlotEmpty),
+    ] {
+        let res = tamagotchi.send(OWNER, action);
+        assert!(res.contains(&Log::builder().dest(OWNER).payload(TmgEvent::Error(error))));
+    }
+
+    let res = tamagotchi.send(OWNER, TmgAction::Unequip(Slot::Accessory));
+    assert!(
+        res.contains(&Log::builder().dest(OWNER).payload(TmgEvent::Unequipped {
+            attribute_id: SWORD,
+            slot: Slot::Accessory,
+        }))
+    );
+    let state = read_state(&tamagotchi);
+    assert!(state.equipment.is_empty());
+    assert_eq!(state.inventory.len(), 2);
+}
+
+#[test]
+fn equipped_attribute_changes_rates() {
+    let sys = System::new();
+    let (_store, tamagotchi) = init(&sys);
+
+    buy_attribute(&tamagotchi, SWORD, TmgEvent::AttributeBought(SWORD));
+    sys.spend_blocks(3000);
+    assert!(!tamagotchi
+        .send(OWNER, TmgAction::Equip(SWORD))
+        .main_failed());
+
+    let equipped = read_state(&tamagotchi);
+    sys.spend_blocks(100);
+    let state = read_state(&tamagotchi);
+    let blocks_passed = state.entertained_block - equipped.entertained_block;
+    assert_eq!(
+        state.entertained,
+        equipped.entertained - blocks_passed * BOREDOM_PER_BLOCK / 2
+    );
+
+    let res = tamagotchi.send(OWNER, TmgAction::Feed);
+    assert!(res.contains(&Log::builder().dest(OWNER).payload(TmgEvent::Fed)));
+    let fed = read_state(&tamagotchi);
+    let blocks_passed = fed.fed_block - state.fed_block;
+    assert_eq!(
+        fed.fed,
+        state.fed - blocks_passed * HUNGER_PER_BLOCK + FILL_PER_FEED + 500
+    );
+
+    // Taking the attribute off restores the base rates.
+    assert!(!tamagotchi
+        .send(OWNER, TmgAction::Unequip(Slot::Accessory))
+        .main_failed());
+    let unequipped = read_state(&tamagotchi);
+    sys.spend_blocks(100);
+    let state = read_state(&tamagotchi);
+    let blocks_passed = state.entertained_block - unequipped.entertained_block;
+    assert_eq!(
+        state.entertained,
+        unequipped.entertained - blocks_passed * BOREDOM_PER_BLOCK
+    );
+}
+
+#[test]
+fn return_equipped_attribute() {
+    let sys = System::new();
+    let (store, tamagotchi) = init(&sys);
+
+    let res = store.send(
+        ADMIN,
+        StoreAction::SetReturnWindow {
+            attribute_id: SWORD,
+            blocks: Some(100),
+        },
+    );
+    assert!(!res.main_failed());
+
+    buy_attribute(&tamagotchi, SWORD, TmgEvent::AttributeBought(SWORD));
+    assert!(!tamagotchi
+        .send(OWNER, TmgAction::Equip(SWORD))
+        .main_failed());
+
+    let res = tamagotchi.send(OWNER, TmgAction::ReturnAttribute(SWORD));
+    assert!(res.contains(
+        &Log::builder()
+            .dest(OWNER)
+            .payload(TmgEvent::AttributeReturned(SWORD))
+    ));
+
+    let state = read_state(&tamagotchi);
+    assert!(state.inventory.is_empty());
+    assert!(state.equipment.is_empty());
+
+    let res = tamagotchi.send(OWNER, TmgAction::ReturnAttribute(SWORD));
+    assert!(res.contains(
+        &Log::builder()
+            .dest(OWNER)
+            .payload(TmgEvent::Error(TmgError::NotInInventory))
+    ));
+}
+
+#[test]
+fn listed_attribute_is_not_equipped() {
+    let sys = System::new();
+    let (_store, tamagotchi) = init(&sys);
+    buy_attribute(&tamagotchi, SWORD, TmgEvent::AttributeBought(SWORD));
+
+    let _marketplace = init_marketplace(&sys);
+
+    let res = tamagotchi.send(
+        OWNER,
+        TmgAction::ListAttribute {
+            marketplace_id: MARKETPLACE_ID.into(),
+            attribute_id: SWORD,
+            price: SWORD_PRICE,
+        },
+    );
+    assert!(res.contains(
+        &Log::builder()
+            .dest(OWNER)
+            .payload(TmgEvent::AttributeListed {
+                attribute_id: SWORD,
+                price: SWORD_PRICE,
+            })
+    ));
+
+    let res = tamagotchi.send(OWNER, TmgAction::Equip(SWORD));
+    assert!(res.contains(
+        &Log::builder()
+            .dest(OWNER)
+            .payload(TmgEvent::Error(TmgError::AttributeListed))
+    ));
+
+    let res = tamagotchi.send(
+        OWNER,
+        TmgAction::CancelListing {
+            marketplace_id: MARKETPLACE_ID.into(),
+            attribute_id: SWORD,
+        },
+    );
+    assert!(res.contains(
+        &Log::builder()
+            .dest(OWNER)
+            .payload(TmgEvent::ListingCancelled(SWORD))
+    ));
+    let state = read_state(&tamagotchi);
+    assert!(state.listed.is_empty());
+
+    let res = tamagotchi.send(OWNER, TmgAction::Equip(SWORD));
+    assert!(
+        res.contains(&Log::builder().dest(OWNER).payload(TmgEvent::Equipped {
+            attribute_id: SWORD,
+            slot: Slot::Accessory,
+        }))
+    );
+}
+
+#[test]
+fn invalid_coupon_is_reported() {
+    let sys = System::new();
+    let (_store, tamagotchi) = init(&sys);
+
+    let res = tamagotchi.send(
+        OWNER,
+        TmgAction::BuyAttribute {
+            store_id: STORE_ID.into(),
+            attribute_id: SWORD,
+            coupon: Some(String::from("UNKNOWN")),
+        },
+    );
+    assert!(res.contains(
+        &Log::builder()
+            .dest(OWNER)
+            .payload(TmgEvent::Error(TmgError::InvalidCoupon))
+    ));
+    let state = read_state(&tamagotchi);
+    assert!(state.inventory.is_empty());
+    assert_eq!(state.pending_purchase, None);
+}
+
+#[test]
+fn return_outside_window_is_rejected() {
+    let sys = System::new();
+    let (_store, tamagotchi) = init(&sys);
+    buy_attribute(&tamagotchi, SWORD, TmgEvent::AttributeBought(SWORD));
+
+    // the sword has no return window
+    let res = tamagotchi.send(OWNER, TmgAction::ReturnAttribute(SWORD));
+    assert!(res.contains(
+        &Log::builder()
+            .dest(OWNER)
+            .payload(TmgEvent::Error(TmgError::ReturnWindowClosed))
+    ));
+    assert_eq!(
+        read_state(&tamagotchi).inventory.get(&SWORD),
+        Some(&STORE_ID.into())
+    );
+}
+
+#[test]
+fn equipped_attribute_is_not_listed() {
+    let sys = System::new();
+    let (_store, tamagotchi) = init(&sys);
+    buy_attribute(&tamagotchi, SWORD, TmgEvent::AttributeBought(SWORD));
+    let _marketplace = init_marketplace(&sys);
+    assert!(!tamagotchi
+        .send(OWNER, TmgAction::Equip(SWORD))
+        .main_failed());
+
+    let res = tamagotchi.send(
+        OWNER,
+        TmgAction::ListAttribute {
+            marketplace_id: MARKETPLACE_ID.into(),
+            attribute_id: SWORD,
+            price: SWORD_PRICE,
+        },
+    );
+    assert!(res.contains(
+        &Log::builder()
+            .dest(OWNER)
+            .payload(TmgEvent::Error(TmgError::AttributeEquipped))
+    ));
+    assert!(read_state(&tamagotchi).listed.is_empty());
+}
+
+#[test]
+fn marketplace_rejection_is_reported() {
+    let sys = System::new();
+    let (_store, tamagotchi) = init(&sys);
+    buy_attribute(&tamagotchi, SWORD, TmgEvent::AttributeBought(SWORD));
+    let _marketplace = init_marketplace(&sys);
+
+    // the marketplace fails on a listing it doesn't have
+    let res = tamagotchi.send(
+        OWNER,
+        TmgAction::CancelListing {
+            marketplace_id: MARKETPLACE_ID.into(),
+            attribute_id: SWORD,
+        },
+    );
+    assert!(res.contains(
+        &Log::builder()
+            .dest(OWNER)
+            .payload(TmgEvent::Error(TmgError::MarketplaceRejected))
+    ));
+    assert_eq!(
+        read_state(&tamagotchi).inventory.get(&SWORD),
+        Some(&STORE_ID.into())
+    );
+}
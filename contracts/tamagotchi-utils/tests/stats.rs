@@ -0,0 +1,167 @@
+mod common;
+
+use common::{init_tamagotchi, read_state, OWNER, STRANGER};
+use gtest::{Log, System};
+use tamagotchi_utils_io::{StatConfig, StatValues, TmgAction, TmgError, TmgEvent};
+
+const MAX_STAT_VALUE: u32 = 10000;
+
+/// Blocks between two `CheckState` messages of the loop.
+const DELAY: u32 = 20;
+const RESERVATION_AMOUNT: u64 = 10_000_000_000;
+
+#[test]
+fn stats_decay_over_blocks() {
+    let sys = System::new();
+    let tamagotchi = init_tamagotchi(&sys, None);
+
+    let birth = read_state(&tamagotchi);
+    assert_eq!(birth.fed, MAX_STAT_VALUE);
+    assert_eq!(birth.entertained, MAX_STAT_VALUE);
+    assert_eq!(birth.slept, MAX_STAT_VALUE);
+
+    // hunger drops by 1 a block, boredom and energy by 2
+    sys.spend_blocks(100);
+
+    let state = read_state(&tamagotchi);
+    assert_eq!(state.fed, 9900);
+    assert_eq!(state.entertained, 9800);
+    assert_eq!(state.slept, 9800);
+}
+
+#[test]
+fn feeding_persists_between_reads() {
+    let sys = System::new();
+    let tamagotchi = init_tamagotchi(&sys, None);
+
+    sys.spend_blocks(3000);
+    assert_eq!(read_state(&tamagotchi).fed, 7000);
+
+    // the feeding happens a block later and adds 1000
+    let res = tamagotchi.send(OWNER, TmgAction::Feed);
+    assert!(res.contains(&Log::builder().dest(OWNER).payload(TmgEvent::Fed)));
+    assert_eq!(read_state(&tamagotchi).fed, 7999);
+
+    sys.spend_blocks(50);
+    assert_eq!(read_state(&tamagotchi).fed, 7949);
+}
+
+#[test]
+fn check_state_does_not_double_decay() {
+    let sys = System::new();
+    let config = StatConfig {
+        boredom_per_block: 0,
+        energy_per_block: 0,
+        ..Default::default()
+    };
+    let tamagotchi = init_tamagotchi(&sys, Some(config));
+
+    sys.spend_blocks(9000);
+
+    let res = tamagotchi.send(OWNER, TmgAction::CheckState);
+    assert!(res.contains(&Log::builder().dest(OWNER).payload(TmgEvent::FeedMe)));
+    assert_eq!(read_state(&tamagotchi).fed, 999);
+
+    sys.spend_blocks(10);
+    assert_eq!(read_state(&tamagotchi).fed, 989);
+}
+
+#[test]
+fn check_needs_reports_every_need() {
+    let sys = System::new();
+    let config = StatConfig {
+        hunger_per_block: 5,
+        boredom_per_block: 5,
+        energy_per_block: 0,
+        ..Default::default()
+    };
+    let tamagotchi = init_tamagotchi(&sys, Some(config));
+
+    // hunger and boredom drop to zero, energy doesn't decay
+    sys.spend_blocks(2100);
+
+    let needs = || TmgEvent::Needs {
+        hungry: true,
+        bored: true,
+        tired: false,
+        values: StatValues {
+            fed: 0,
+            entertained: 0,
+            slept: MAX_STAT_VALUE,
+        },
+    };
+    let res = tamagotchi.send(OWNER, TmgAction::CheckNeeds);
+    assert!(res.contains(&Log::builder().dest(OWNER).payload(needs())));
+
+    // the loop reports both needs in one notification
+    let res = tamagotchi.send(
+        OWNER,
+        TmgAction::ReserveGas {
+            reservation_amount: RESERVATION_AMOUNT,
+            duration: 1000,
+        },
+    );
+    assert!(res.contains(&Log::builder().dest(OWNER).payload(TmgEvent::GasReserved)));
+    sys.spend_blocks(DELAY);
+
+    let mailbox = sys.get_mailbox(OWNER);
+    assert!(mailbox.contains(&Log::builder().dest(OWNER).payload(needs())));
+    assert!(!mailbox.contains(&Log::builder().dest(OWNER).payload(TmgEvent::FeedMe)));
+}
+
+#[test]
+fn custom_config_drives_decay_and_fill() {
+    let sys = System::new();
+    let config = StatConfig {
+        hunger_per_block: 5,
+        fill_per_feed: 300,
+        max_stat_value: 5000,
+        ..Default::default()
+    };
+    let tamagotchi = init_tamagotchi(&sys, Some(config));
+
+    let birth = read_state(&tamagotchi);
+    assert_eq!(birth.config, config);
+    assert_eq!(birth.fed, 5000);
+
+    sys.spend_blocks(100);
+    assert_eq!(read_state(&tamagotchi).fed, 4500);
+
+    let res = tamagotchi.send(OWNER, TmgAction::Feed);
+    assert!(res.contains(&Log::builder().dest(OWNER).payload(TmgEvent::Fed)));
+    assert_eq!(read_state(&tamagotchi).fed, 4795);
+}
+
+#[test]
+fn only_owner_updates_config() {
+    let sys = System::new();
+    let tamagotchi = init_tamagotchi(&sys, None);
+    let config = StatConfig {
+        boredom_per_block: 10,
+        max_stat_value: 8000,
+        ..Default::default()
+    };
+
+    let res = tamagotchi.send(STRANGER, TmgAction::UpdateConfig(config));
+    assert!(res.contains(
+        &Log::builder()
+            .dest(STRANGER)
+            .payload(TmgEvent::Error(TmgError::NotOwner))
+    ));
+    assert_eq!(read_state(&tamagotchi).config, StatConfig::default());
+
+    let res = tamagotchi.send(OWNER, TmgAction::UpdateConfig(config));
+    assert!(res.contains(
+        &Log::builder()
+            .dest(OWNER)
+            .payload(TmgEvent::ConfigUpdated(config))
+    ));
+
+    // the stats are capped at the new maximum
+    let updated = read_state(&tamagotchi);
+    assert_eq!(updated.config, config);
+    assert_eq!(updated.entertained, 8000);
+
+    sys.spend_blocks(20);
+    assert_eq!(read_state(&tamagotchi).entertained, 7800);
+}